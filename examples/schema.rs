@@ -3,8 +3,8 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
-use ibc_name_service::msg::{NftInfoResponse, ContractInfoResponse, NumTokensResponse, ApprovedForAllResponse, NameResponse, BlocksResponse, OwnerResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
-use ibc_name_service::state::State;
+use ibc_name_service::msg::{PriceOverridesResponse, ReservedResponse, QuarantinedResponse, RegistrationQuoteResponse, AuctionsResponse, SubdomainPriceResponse, AddressResponse, RecordsResponse, NameStatusResponse, TokensResponse, ReverseResolveResponse, PriceResponse, NftInfoResponse, ContractInfoResponse, NumTokensResponse, ApprovedForAllResponse, NameResponse, BlocksResponse, OwnerResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
+use ibc_name_service::state::{Auction, State};
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -15,6 +15,7 @@ fn main() {
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
     export_schema(&schema_for!(State), &out_dir);
     export_schema(&schema_for!(BlocksResponse), &out_dir);
     export_schema(&schema_for!(OwnerResponse), &out_dir);
@@ -23,5 +24,18 @@ fn main() {
     export_schema(&schema_for!(NumTokensResponse), &out_dir);
     export_schema(&schema_for!(ContractInfoResponse), &out_dir);
     export_schema(&schema_for!(NftInfoResponse), &out_dir);
+    export_schema(&schema_for!(PriceResponse), &out_dir);
+    export_schema(&schema_for!(ReverseResolveResponse), &out_dir);
+    export_schema(&schema_for!(TokensResponse), &out_dir);
+    export_schema(&schema_for!(NameStatusResponse), &out_dir);
+    export_schema(&schema_for!(RecordsResponse), &out_dir);
+    export_schema(&schema_for!(AddressResponse), &out_dir);
+    export_schema(&schema_for!(SubdomainPriceResponse), &out_dir);
+    export_schema(&schema_for!(AuctionsResponse), &out_dir);
+    export_schema(&schema_for!(Auction), &out_dir);
+    export_schema(&schema_for!(RegistrationQuoteResponse), &out_dir);
+    export_schema(&schema_for!(ReservedResponse), &out_dir);
+    export_schema(&schema_for!(QuarantinedResponse), &out_dir);
+    export_schema(&schema_for!(PriceOverridesResponse), &out_dir);
 
 }
@@ -0,0 +1,138 @@
+use crate::error::ContractError;
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const GENERATOR: [u32; 5] = [0x3b6a_57b2, 0x2650_8e6d, 0x1ea1_19fa, 0x3d42_33dd, 0x2a14_62b3];
+const CHECKSUM_LENGTH: usize = 6;
+const MAX_LENGTH: usize = 90;
+
+fn polymod(values: impl Iterator<Item = u8>) -> u32 {
+    let mut chk: u32 = 1;
+    for v in values {
+        let top = chk >> 25;
+        chk = ((chk & 0x01ff_ffff) << 5) ^ u32::from(v);
+        for (i, g) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= g;
+            }
+        }
+    }
+    chk
+}
+
+fn hrp_expand(hrp: &str) -> impl Iterator<Item = u8> + '_ {
+    hrp.bytes()
+        .map(|b| b >> 5)
+        .chain(std::iter::once(0))
+        .chain(hrp.bytes().map(|b| b & 0x1f))
+}
+
+/// Regroups 5 bit words into bytes, rejecting non-zero padding.
+fn to_bytes(data: &[u8]) -> Option<Vec<u8>> {
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+    let mut bytes = Vec::with_capacity(data.len() * 5 / 8);
+    for v in data {
+        acc = (acc << 5) | u32::from(*v);
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((acc >> bits) as u8);
+        }
+    }
+    if bits >= 5 || (acc << (8 - bits)) & 0xff != 0 {
+        return None;
+    }
+    Some(bytes)
+}
+
+/// Decodes a bech32 address like `juno1...` into its human readable prefix
+/// and payload. Mixed case is rejected and the prefix comes back lowercase.
+pub fn decode(address: &str) -> Result<(String, Vec<u8>), ContractError> {
+    let invalid = |reason: &str| ContractError::InvalidAddress {
+        address: address.to_string(),
+        reason: reason.to_string(),
+    };
+
+    if address.len() > MAX_LENGTH {
+        return Err(invalid(&format!("must be at most {} characters", MAX_LENGTH)));
+    }
+    if address.bytes().any(|b| !(33..=126).contains(&b)) {
+        return Err(invalid("contains characters outside of printable ascii"));
+    }
+    if address.bytes().any(|b| b.is_ascii_lowercase()) && address.bytes().any(|b| b.is_ascii_uppercase()) {
+        return Err(invalid("mixes upper and lower case"));
+    }
+    let address_lower = address.to_ascii_lowercase();
+
+    let split = address_lower.rfind('1').ok_or_else(|| invalid("missing separator"))?;
+    let (hrp, data) = (&address_lower[..split], &address_lower[split + 1..]);
+    if hrp.is_empty() {
+        return Err(invalid("missing prefix"));
+    }
+    if data.len() < CHECKSUM_LENGTH {
+        return Err(invalid("too short"));
+    }
+
+    let mut values = Vec::with_capacity(data.len());
+    for c in data.bytes() {
+        match CHARSET.iter().position(|x| *x == c) {
+            Some(v) => values.push(v as u8),
+            None => return Err(invalid(&format!("character {:?} is not allowed", c as char))),
+        }
+    }
+
+    if polymod(hrp_expand(hrp).chain(values.iter().copied())) != 1 {
+        return Err(invalid("checksum does not match"));
+    }
+
+    let payload = &values[..values.len() - CHECKSUM_LENGTH];
+    let bytes = to_bytes(payload).ok_or_else(|| invalid("invalid padding"))?;
+    if bytes.is_empty() {
+        return Err(invalid("empty payload"));
+    }
+
+    Ok((hrp.to_string(), bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_valid_addresses() {
+        let payload: Vec<u8> = (1..=20).collect();
+        let valid = [
+            ("juno", "juno1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5fs09pq"),
+            ("osmo", "osmo1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5helwsw"),
+            ("cosmos", "cosmos1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5lzv7xu"),
+            ("cosmos", "COSMOS1QYPQXPQ9QCRSSZG2PVXQ6RS0ZQG3YYC5LZV7XU"),
+        ];
+        for (prefix, address) in valid.iter() {
+            assert_eq!((prefix.to_string(), payload.clone()), decode(address).unwrap());
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_addresses() {
+        let invalid = [
+            "",
+            "noseparator",
+            "1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5fs09pq",
+            "juno1short",
+            // last character changed
+            "juno1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5fs09pp",
+            // 'b' is not in the charset
+            "juno1bypqxpq9qcrsszg2pvxq6rs0zqg3yyc5fs09pq",
+            "Juno1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5fs09pq",
+            "juno1 qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5fs09pq",
+        ];
+        for address in invalid.iter() {
+            match decode(address) {
+                Err(ContractError::InvalidAddress { .. }) => {}
+                res => panic!("{:?} must be rejected, got {:?}", address, res),
+            }
+        }
+
+        assert!(decode(&format!("juno1{}", "q".repeat(MAX_LENGTH))).is_err());
+    }
+}
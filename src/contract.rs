@@ -1,11 +1,16 @@
 #[cfg(not(feature = "library"))]
-use cosmwasm_std::{Timestamp, entry_point, BankMsg, from_binary, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Addr, Coin, Uint128, StdError, CosmosMsg, CanonicalAddr};
-use cw2::set_contract_version;
-use cw_utils::{ NativeBalance };
+use cosmwasm_std::{entry_point, BankMsg, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Addr, Coin, OverflowError, Uint64, Uint128, StdError, CanonicalAddr, Storage, Order, Empty};
+use cw2::{get_contract_version, set_contract_version};
+use cw_storage_plus::Bound;
+use cw_utils::{must_pay, PaymentError};
+use sha2::{Digest, Sha256};
 
 use crate::error::ContractError;
-use crate::msg::{NftInfoResponse, ContractInfoResponse, NumTokensResponse, ApprovedForAllResponse, OwnerResponse, BlocksResponse, NameResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{State, OPERATORS, Operator, STATE, JNS, Name, Approval};
+use crate::msg::{Cw721ReceiveMsg, NftInfoResponse, ContractInfoResponse, NumTokensResponse, ApprovedForAllResponse, OwnerResponse, BlocksResponse, NameResponse, PriceResponse, DenomQuote, MainDenomOnly, AddressResponse, AddressUpdate, RecordUpdate, AuctionsResponse, RegistrationQuoteResponse, ReservedResponse, QuarantinedResponse, NamePrice, PriceOverridesResponse, NameStatusResponse, SubdomainPriceResponse, RecordsResponse, ReverseResolveResponse, TokensResponse, ConfigUpdate, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
+use crate::migrations::{self, parse_version};
+use crate::bech32;
+use crate::normalize::{normalize_full_name, normalize_name, parent_name};
+use crate::state::{ChainAddress, TextRecord, State, OPERATORS, Operator, STATE, jns, NameStatus, Commitment, commitments, PRIMARY_NAMES, SUBDOMAIN_PRICES, SOLD_SUBDOMAINS, AUCTIONS, Auction, PremiumDecay, RESERVED, PRICE_OVERRIDES, TOKEN_COUNT, decrement_tokens, increment_tokens, num_tokens, Name, Approval, PriceTier, DenomPrices};
 
 use cw_utils::Expiration;
 
@@ -14,6 +19,267 @@ const CONTRACT_NAME: &str = "crates.io:ibc_name_service";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 const SECONDS_IN_YEAR: u64 = 365 * 24 * 60 * 60;
+const SECONDS_IN_DAY: u64 = 24 * 60 * 60;
+const NANOS_IN_SECOND: u64 = 1_000_000_000;
+
+pub const DEFAULT_MIN_COMMITMENT_AGE: u64 = 60;
+pub const DEFAULT_MAX_COMMITMENT_AGE: u64 = 24 * 60 * 60;
+// expired commitments a single CommitName clears out
+const MAX_PRUNED_COMMITMENTS: usize = 10;
+
+pub const DEFAULT_AUCTION_DURATION: u64 = 7 * 24 * 60 * 60;
+pub const DEFAULT_MIN_BID_INCREMENT_BPS: u64 = 500;
+
+pub const DEFAULT_MAX_REGISTRATION_YEARS: u64 = 10;
+
+// limits keeping a name's records bounded
+const MAX_RECORDS: usize = 32;
+const MAX_RECORD_KEY_LENGTH: usize = 64;
+const MAX_RECORD_VALUE_LENGTH: usize = 1024;
+const MAX_ADDRESSES: usize = 32;
+
+const BPS_DENOMINATOR: u64 = 10_000;
+
+// legacy names rewritten by a single migrate call
+const DEFAULT_MIGRATION_LIMIT: u32 = 100;
+
+// pagination for the enumeration queries
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+fn default_prices() -> Vec<PriceTier> {
+    vec![
+        PriceTier { min_length: 1, cost: Uint128::new(5000000) },
+        PriceTier { min_length: 2, cost: Uint128::new(2500000) },
+        PriceTier { min_length: 3, cost: Uint128::new(1250000) },
+        PriceTier { min_length: 4, cost: Uint128::new(625000) },
+        PriceTier { min_length: 5, cost: Uint128::new(312500) },
+        PriceTier { min_length: 6, cost: Uint128::new(156250) },
+    ]
+}
+
+/// Tiers must be sorted by strictly increasing `min_length` and start at 1 so
+/// that every name length has a price.
+fn validate_prices(prices: &[PriceTier]) -> Result<(), ContractError> {
+    match prices.first() {
+        Some(tier) if tier.min_length == 1 => {}
+        _ => {
+            return Err(ContractError::InvalidConfig {
+                reason: String::from("price tiers must start at a length of 1"),
+            })
+        }
+    }
+
+    if prices.windows(2).any(|w| w[0].min_length >= w[1].min_length) {
+        return Err(ContractError::InvalidConfig {
+            reason: String::from("price tiers must be ordered by increasing length"),
+        });
+    }
+
+    Ok(())
+}
+
+/// Every extra denom needs valid tiers and can only be listed once, next to
+/// the main `denom`.
+fn validate_extra_denoms(denom: &str, extra_denoms: &[DenomPrices]) -> Result<(), ContractError> {
+    for (i, extra) in extra_denoms.iter().enumerate() {
+        if extra.denom.is_empty() {
+            return Err(ContractError::InvalidConfig { reason: String::from("denom cannot be empty") });
+        }
+        if extra.denom == denom || extra_denoms[..i].iter().any(|d| d.denom == extra.denom) {
+            return Err(ContractError::InvalidConfig { reason: format!("denom {} is listed twice", extra.denom) });
+        }
+        validate_prices(&extra.prices)?;
+    }
+    Ok(())
+}
+
+fn validate_commitment_ages(min_age: u64, max_age: u64) -> Result<(), ContractError> {
+    if min_age >= max_age {
+        return Err(ContractError::InvalidConfig { reason: String::from("commitments must become usable before they expire") });
+    }
+    Ok(())
+}
+
+fn validate_fee_bps(fee: u64) -> Result<(), ContractError> {
+    if fee > BPS_DENOMINATOR {
+        return Err(ContractError::InvalidConfig { reason: format!("fees cannot exceed {} basis points", BPS_DENOMINATOR) });
+    }
+    Ok(())
+}
+
+fn validate_max_registration_years(years: u64) -> Result<(), ContractError> {
+    if years == 0 {
+        return Err(ContractError::InvalidConfig { reason: String::from("names must be registrable for at least a year") });
+    }
+    Ok(())
+}
+
+/// `time` in nanoseconds moved `seconds` later, failing rather than wrapping.
+pub fn add_seconds(time: u64, seconds: u64) -> Result<u64, OverflowError> {
+    let nanos = Uint64::from(seconds).checked_mul(Uint64::from(NANOS_IN_SECOND))?;
+    Ok(Uint64::from(time).checked_add(nanos)?.u64())
+}
+
+/// `time` in nanoseconds moved `years` years later, failing rather than wrapping.
+pub fn add_years(time: u64, years: u64) -> Result<u64, OverflowError> {
+    let seconds = Uint64::from(SECONDS_IN_YEAR).checked_mul(Uint64::from(years))?;
+    add_seconds(time, seconds.u64())
+}
+
+/// Cost of `years` years of a name at `cost_per_year`.
+pub fn total_price(cost_per_year: Uint128, years: u64) -> Result<Uint128, OverflowError> {
+    cost_per_year.checked_mul(Uint128::from(years))
+}
+
+/// Registrations and renewals can't push `expires` past `max_registration_years` from now.
+fn check_registration_horizon(state: &State, now: u64, expires: u64) -> Result<(), ContractError> {
+    // a horizon past the end of time does not limit anything
+    let horizon = add_years(now, state.max_registration_years).unwrap_or(u64::MAX);
+    if expires > horizon {
+        return Err(ContractError::TooFarAhead { max_years: state.max_registration_years });
+    }
+    Ok(())
+}
+
+/// Splits a subdomain sale into the protocol fee and the parent owner's share.
+pub fn split_subdomain_price(price: Uint128, fee_bps: u64) -> (Uint128, Uint128) {
+    let fee = price.multiply_ratio(fee_bps, BPS_DENOMINATOR);
+    (fee, price - fee)
+}
+
+/// Yearly cost of a name, taken from the last tier whose threshold it reaches.
+pub fn price_per_year(prices: &[PriceTier], name: &str) -> Uint128 {
+    let char_count = name.chars().count() as u32;
+
+    prices
+        .iter()
+        .rev()
+        .find(|tier| tier.min_length <= char_count)
+        .map(|tier| tier.cost)
+        .unwrap_or_default()
+}
+
+/// Premium on top of the base price for registering a name that was released
+/// at `released`, the end of its redemption period, falling from
+/// `state.premium` to zero over `state.premium_days`.
+pub fn expiry_premium(state: &State, released: u64, now: u64) -> Uint128 {
+    let period = u128::from(state.premium_days) * u128::from(SECONDS_IN_DAY) * u128::from(NANOS_IN_SECOND);
+    let elapsed = u128::from(now.saturating_sub(released));
+    if now < released || elapsed >= period {
+        return Uint128::zero();
+    }
+
+    match state.premium_decay {
+        PremiumDecay::Linear => state.premium.multiply_ratio(period - elapsed, period),
+        PremiumDecay::Exponential => {
+            // halve once per day, interpolating linearly within the day, then
+            // subtract what is left at the end so the premium ends at zero
+            let day = u128::from(SECONDS_IN_DAY) * u128::from(NANOS_IN_SECOND);
+            let halved = |elapsed: u128| -> Uint128 {
+                let days = elapsed / day;
+                if days >= 128 {
+                    return Uint128::zero();
+                }
+                let start = state.premium.u128() >> days;
+                Uint128::new(start) - Uint128::new(start / 2).multiply_ratio(elapsed % day, day)
+            };
+            halved(elapsed).saturating_sub(halved(period))
+        }
+    }
+}
+
+/// Errors unless the previous registration lapsed for good and the name can
+/// go to someone new.
+fn check_available(existing: &Name, state: &State, now: u64) -> Result<(), ContractError> {
+    match existing.status(state, now) {
+        NameStatus::Active => Err(ContractError::NameTaken { name: existing.id.clone() }),
+        NameStatus::Grace | NameStatus::Redemption => Err(ContractError::InRenewalPeriod { name: existing.id.clone() }),
+        NameStatus::Available => Ok(()),
+    }
+}
+
+/// Checks the funds sent cover `cost` in `denom`, returning a refund of
+/// anything sent on top. Funds in any other denom are rejected.
+fn take_payment(info: &MessageInfo, denom: &str, cost: Uint128) -> Result<Option<BankMsg>, ContractError> {
+    if let Some(coin) = info.funds.iter().find(|c| c.denom != denom) {
+        return Err(ContractError::UnsupportedDenom { denom: coin.denom.clone() });
+    }
+
+    let paid = info.funds.iter().try_fold(Uint128::zero(), |paid, c| paid.checked_add(c.amount))?;
+    if paid < cost {
+        return Err(ContractError::InsufficientFunds { required: cost, denom: denom.to_string() });
+    }
+
+    let excess = paid - cost;
+    if excess.is_zero() {
+        return Ok(None);
+    }
+
+    Ok(Some(BankMsg::Send {
+        to_address: info.sender.to_string(),
+        amount: vec![Coin { denom: denom.to_string(), amount: excess }],
+    }))
+}
+
+/// Yearly cost of a name, its own price if the admin set one or else its tier.
+pub fn yearly_price(storage: &dyn Storage, state: &State, name: &str) -> StdResult<Uint128> {
+    match PRICE_OVERRIDES.may_load(storage, name)? {
+        Some(cost) => Ok(cost),
+        None => Ok(price_per_year(&state.prices, name)),
+    }
+}
+
+/// Yearly cost of a name in `denom`, `None` when it can't be paid in it. Names
+/// with their own price are only sold in the main denom.
+pub fn yearly_price_in(storage: &dyn Storage, state: &State, name: &str, denom: &str) -> StdResult<Option<Uint128>> {
+    if denom == state.denom {
+        return yearly_price(storage, state, name).map(Some);
+    }
+    if PRICE_OVERRIDES.has(storage, name) {
+        return Ok(None);
+    }
+    Ok(state.extra_denoms.iter().find(|d| d.denom == denom).map(|d| price_per_year(&d.prices, name)))
+}
+
+/// The denom the sender picked by the funds they sent, the main one if they sent none.
+fn payment_denom(info: &MessageInfo, state: &State) -> Result<String, ContractError> {
+    match info.funds.as_slice() {
+        [] => Ok(state.denom.clone()),
+        [coin] => Ok(coin.denom.clone()),
+        _ => Err(ContractError::Payment(PaymentError::MultipleDenoms {})),
+    }
+}
+
+/// Why `name` can't be paid in the extra denoms right now, `None` if it can
+/// or there are none to pay in.
+fn main_denom_only(storage: &dyn Storage, state: &State, name: &str, premium: Uint128) -> StdResult<Option<MainDenomOnly>> {
+    if state.extra_denoms.is_empty() {
+        return Ok(None);
+    }
+    if PRICE_OVERRIDES.has(storage, name) {
+        return Ok(Some(MainDenomOnly::PriceOverride));
+    }
+    if !premium.is_zero() {
+        return Ok(Some(MainDenomOnly::ExpiryPremium));
+    }
+    Ok(None)
+}
+
+/// What `years` of `name` costs in every extra denom it can be paid in.
+fn extra_denom_quotes(storage: &dyn Storage, state: &State, name: &str, years: u64) -> StdResult<Vec<DenomQuote>> {
+    let mut quotes = vec![];
+    for extra in state.extra_denoms.iter() {
+        if let Some(cost_per_year) = yearly_price_in(storage, state, name, &extra.denom)? {
+            quotes.push(DenomQuote {
+                denom: extra.denom.clone(),
+                cost_per_year,
+                total: total_price(cost_per_year, years)?,
+            });
+        }
+    }
+    Ok(quotes)
+}
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -28,45 +294,30 @@ pub fn instantiate(
         meta_url: msg.meta_url.to_string(),
         denom: msg.denom.to_string(),
         //prices to register per character count
-        cost_for_6: {
-            match msg.cost_for_6 {
-                Some(x) => {x},
-                None => {156250}
-            }
-        },
-        cost_for_5: {
-            match msg.cost_for_5 {
-                Some(x) => {x},
-                None => {312500}
-            }
-        },
-        cost_for_4: {
-            match msg.cost_for_4 {
-                Some(x) => {x},
-                None => {625000}
-            }
-        },
-        cost_for_3: {
-            match msg.cost_for_3 {
-                Some(x) => {x},
-                None => {1250000}
-            }
-        },
-        cost_for_2: {
-            match msg.cost_for_2 {
-                Some(x) => {x},
-                None => {2500000}
-            }
-        },
-        cost_for_1: {
-            match msg.cost_for_1 {
-                Some(x) => {x},
-                None => {5000000}
-            }
-        },
+        prices: msg.prices.unwrap_or_else(default_prices),
+        grace_period: msg.grace_period.unwrap_or(0),
+        redemption_period: msg.redemption_period.unwrap_or(0),
+        redemption_fee: msg.redemption_fee.unwrap_or_default(),
+        require_commitment: msg.require_commitment.unwrap_or(false),
+        min_commitment_age: msg.min_commitment_age.unwrap_or(DEFAULT_MIN_COMMITMENT_AGE),
+        max_commitment_age: msg.max_commitment_age.unwrap_or(DEFAULT_MAX_COMMITMENT_AGE),
+        subdomain_fee_bps: msg.subdomain_fee_bps.unwrap_or(0),
+        auction_duration: msg.auction_duration.unwrap_or(DEFAULT_AUCTION_DURATION),
+        min_bid_increment_bps: msg.min_bid_increment_bps.unwrap_or(DEFAULT_MIN_BID_INCREMENT_BPS),
+        premium: msg.premium.unwrap_or_default(),
+        premium_days: msg.premium_days.unwrap_or(0),
+        premium_decay: msg.premium_decay.unwrap_or(PremiumDecay::Linear),
+        max_registration_years: msg.max_registration_years.unwrap_or(DEFAULT_MAX_REGISTRATION_YEARS),
+        extra_denoms: msg.extra_denoms.unwrap_or_default(),
     };
+    validate_prices(&state.prices)?;
+    validate_extra_denoms(&state.denom, &state.extra_denoms)?;
+    validate_commitment_ages(state.min_commitment_age, state.max_commitment_age)?;
+    validate_fee_bps(state.subdomain_fee_bps)?;
+    validate_max_registration_years(state.max_registration_years)?;
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     STATE.save(deps.storage, &state)?;
+    TOKEN_COUNT.save(deps.storage, &0)?;
 
     Ok(Response::new()
         .add_attribute("method", "instantiate")
@@ -74,6 +325,42 @@ pub fn instantiate(
         .add_attribute("blocks_per_year", msg.blocks_per_year.to_string()))
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::CannotMigrate { previous_contract: stored.contract });
+    }
+
+    let stored_version = parse_version(&stored.version)?;
+    if stored_version > parse_version(CONTRACT_VERSION)? {
+        return Err(ContractError::CannotDowngrade { stored: stored.version, current: CONTRACT_VERSION.to_string() });
+    }
+
+    // rewrite entries saved by older layouts, oldest first
+    let limit = msg.limit.unwrap_or(DEFAULT_MIGRATION_LIMIT).max(1) as usize;
+    let mut migrated = migrations::Migrated { done: true, ..migrations::Migrated::default() };
+    if stored_version <= migrations::v0_1_0::VERSION {
+        migrated = migrations::v0_1_0::migrate(deps.storage, limit)?;
+    }
+
+    // the stored version stays put until the last batch, so the next call resumes
+    if migrated.done {
+        set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    }
+
+    // one `quarantined` attribute per name set aside, quoted as ids may be empty
+    let quarantined = migrated.quarantined.iter().map(|id| ("quarantined", format!("{:?}", id)));
+
+    Ok(Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION)
+        .add_attribute("names_migrated", migrated.names.to_string())
+        .add_attribute("migration_done", migrated.done.to_string())
+        .add_attributes(quarantined))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -81,14 +368,47 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // names past the cursor are still in the old layout
+    if migrations::MIGRATION_CURSOR.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::MigrationInProgress {});
+    }
+
     match msg {
         ExecuteMsg::SetBlocksPerYear { blocks_per_year} => try_set_blocks_per_year(deps, info, blocks_per_year),
         ExecuteMsg::SetOwner { owner } => try_set_owner(deps, info, owner),
-        ExecuteMsg::RegisterName { name, years , avatar_url, terra_address, secret_address, crypto_org_address, starname_address, persistence_address, kava_address, website, email, twitter, telegram, discord, instagram, reddit} => try_register_name(deps, env, info, name, years, avatar_url, terra_address, secret_address, crypto_org_address, starname_address, persistence_address, kava_address, website, email, twitter, telegram, discord, instagram, reddit),
+        ExecuteMsg::UpdateConfig(update) => try_update_config(deps, info, update),
+        ExecuteMsg::RegisterName { name, years , records, addresses} => {
+            if STATE.load(deps.storage)?.require_commitment {
+                return Err(ContractError::CommitmentRequired {});
+            }
+            try_register_name(deps, env, info, name, years, records, addresses)
+        },
+        ExecuteMsg::CommitName { commitment } => try_commit_name(deps, env, info, commitment),
+        ExecuteMsg::RevealName { name, secret, years , records, addresses} => {
+            check_commitment(deps.storage, &env, &info, &name, &secret)?;
+            try_register_name(deps, env, info, name, years, records, addresses)
+        },
         ExecuteMsg::AddTime { name, years} => try_add_time(deps, env, info, name, years),
-        ExecuteMsg::UpdateParams { name, avatar_url, terra_address, secret_address, crypto_org_address, starname_address, persistence_address, kava_address, website, email, twitter, telegram, discord, instagram, reddit} => try_update_name(deps, env, info, name, avatar_url, terra_address, secret_address, crypto_org_address, starname_address, persistence_address, kava_address, website, email, twitter, telegram, discord, instagram, reddit),
-        ExecuteMsg::TransferNft {recipient, token_id} => transfer_nft (deps, env, info, recipient, token_id),
-        ExecuteMsg::SendNft {contract, token_id, message} => try_send_nft (deps, env, info, contract, token_id, message),
+        ExecuteMsg::SetPrimaryName { name } => try_set_primary_name(deps, env, info, name),
+        ExecuteMsg::UpdateParams { name, records, addresses} => try_update_name(deps, env, info, name, records, addresses),
+        ExecuteMsg::SetRecord { name, key, value } => try_set_record(deps, env, info, name, key, value),
+        ExecuteMsg::RemoveRecord { name, key } => try_remove_record(deps, env, info, name, key),
+        ExecuteMsg::CreateSubdomain { parent, label, owner, expires } => try_create_subdomain(deps, env, info, parent, label, owner, expires),
+        ExecuteMsg::ExtendSubdomain { name, expires } => try_extend_subdomain(deps, env, info, name, expires),
+        ExecuteMsg::RevokeSubdomain { name } => try_revoke_subdomain(deps, env, info, name),
+        ExecuteMsg::SetSubdomainPrice { parent, price } => try_set_subdomain_price(deps, env, info, parent, price),
+        ExecuteMsg::BuySubdomain { parent, label } => try_buy_subdomain(deps, env, info, parent, label),
+        ExecuteMsg::StartAuction { name, min_bid } => try_start_auction(deps, env, info, name, min_bid),
+        ExecuteMsg::Bid { name } => try_bid(deps, env, info, name),
+        ExecuteMsg::CloseAuction { name } => try_close_auction(deps, env, name),
+        ExecuteMsg::AddReserved { names } => try_add_reserved(deps, info, names),
+        ExecuteMsg::RemoveReserved { names } => try_remove_reserved(deps, info, names),
+        ExecuteMsg::MintReserved { name, owner, years } => try_mint_reserved(deps, env, info, name, owner, years),
+        ExecuteMsg::ReleaseQuarantined { id, name } => try_release_quarantined(deps, env, info, id, name),
+        ExecuteMsg::SetPriceOverrides { prices } => try_set_price_overrides(deps, info, prices),
+        ExecuteMsg::RemovePriceOverrides { names } => try_remove_price_overrides(deps, info, names),
+        ExecuteMsg::TransferNft {recipient, token_id, keep_records} => transfer_nft (deps, env, info, recipient, token_id, keep_records.unwrap_or(false)),
+        ExecuteMsg::SendNft {contract, token_id, msg, keep_records} => try_send_nft (deps, env, info, contract, token_id, msg, keep_records.unwrap_or(false)),
         ExecuteMsg::Approve {spender, token_id, expires} => handle_approve (deps, env, info, spender, token_id, expires),
         ExecuteMsg::Revoke {spender, token_id} => handle_revoke (deps, env, info, spender, token_id),
         ExecuteMsg::ApproveAll {operator, expires} => handle_approve_all (deps, env, info, operator, expires),
@@ -108,24 +428,40 @@ pub fn handle_withdraw_balance(
 
 
     if info.sender != state.owner {
-        return Err(ContractError::Std(StdError::generic_err(
-            "Not authorized to withdraw payment.",
-        )));
+        return Err(ContractError::Unauthorized {});
     }
 
-    // get balance and send all to recipient
-    let balance = deps.querier.query_all_balances(env.contract.address)?;
-    let send = BankMsg::Send {
-        to_address: info.sender.to_string(),
-        amount: balance.clone(),
-    };
+    // get balance and send all to recipient, except bids held for open auctions
+    let mut balance = deps.querier.query_all_balances(env.contract.address)?;
+    for auction in AUCTIONS.range(deps.storage, None, None, Order::Ascending) {
+        let (_, auction) = auction?;
+        if auction.highest_bidder.is_none() {
+            continue;
+        }
+        if let Some(coin) = balance.iter_mut().find(|c| c.denom == auction.denom) {
+            coin.amount = coin.amount.saturating_sub(auction.highest_bid);
+        }
+    }
+    balance.retain(|c| !c.amount.is_zero());
 
     let data_msg = format!("{:?}", balance).into_bytes();
 
-    Ok(Response::new()
-        .add_message(send)
+    // one `withdrawn` attribute per denom, like `100ujuno`
+    let withdrawn: Vec<_> = balance.iter().map(|coin| ("withdrawn", coin.to_string())).collect();
+
+    // a bank send without coins fails, only escrow left means nothing to send
+    let mut res = Response::new();
+    if !balance.is_empty() {
+        res = res.add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: balance,
+        });
+    }
+
+    Ok(res
         .add_attribute("action", "withdraw")
         .add_attribute("payed_to", info.sender.to_string())
+        .add_attributes(withdrawn)
         .set_data(data_msg))
     }
 
@@ -140,14 +476,12 @@ pub fn handle_approve_all(
     let expires = expires.unwrap_or_default();
 
     if expires.is_expired(&env.block) {
-        return Err(ContractError::Std(StdError::generic_err(
-            "Cannot set approval that is already expired",
-        )));
+        return Err(ContractError::AlreadyExpired {});
     }
 
     let op = Operator {
         owner: operator.clone(),
-        expires: expires,
+        expires,
     };
 
     let mut ops = OPERATORS.load(deps.storage, info.sender.to_string()).unwrap_or(vec![]);
@@ -178,13 +512,9 @@ pub fn handle_revoke(
     spender: String,
     token_id: String,
 ) -> Result<Response, ContractError> {
-    let token_id = token_id.to_lowercase();
-
-    let approve = _update_approvals(deps, env, info.sender.to_string(), spender.clone(), token_id.clone(), false, None);
+    let token_id = normalize_full_name(&token_id)?;
 
-    if approve.is_err() {
-        return Err(ContractError::Std(StdError::generic_err(approve.unwrap_err().to_string())));
-    }
+    _update_approvals(deps, env, info.sender.to_string(), spender.clone(), token_id.clone(), false, None)?;
 
     Ok(Response::new().add_attribute("action", "revoke").add_attribute("sender", info.sender).add_attribute("spender", spender).add_attribute("token_id", token_id))
 }
@@ -197,13 +527,9 @@ pub fn handle_approve(
     token_id: String,
     expires: Option<Expiration>,
 ) -> Result<Response, ContractError> {
-    let token_id = token_id.to_lowercase();
-
-    let approve = _update_approvals(deps, env, info.sender.to_string(), spender.clone(), token_id.clone(), true, expires);
+    let token_id = normalize_full_name(&token_id)?;
 
-    if approve.is_err() {
-        return Err(ContractError::Std(StdError::generic_err(approve.unwrap_err().to_string())));
-    }
+    _update_approvals(deps, env, info.sender.to_string(), spender.clone(), token_id.clone(), true, expires)?;
 
     Ok(Response::new().add_attribute("action", "approve").add_attribute("sender", info.sender).add_attribute("spender", spender).add_attribute("token_id", token_id))
 }
@@ -217,15 +543,12 @@ pub fn _update_approvals(
     add: bool,
     expires: Option<Expiration>,
 ) -> Result<NameResponse, ContractError> {
-    let token_id = token_id.to_lowercase();
-
-    let token = JNS.may_load(deps.storage, &token_id);
+    let token_id = normalize_full_name(&token_id)?;
 
-    if token.is_err() {
-        return Err(ContractError::Std(StdError::generic_err(token.unwrap_err().to_string())));
-    }
-
-    let mut token = token.unwrap().unwrap();
+    let mut token = match jns().may_load(deps.storage, &token_id)? {
+        Some(t) => t,
+        None => return Err(ContractError::NameNotFound { name: token_id }),
+    };
 
     if token.owner != sender {
         return Err(ContractError::Unauthorized {});
@@ -235,20 +558,14 @@ pub fn _update_approvals(
     // update the approval list (remove any for the same spender before adding)
     let spender_raw = deps.api.addr_canonicalize(&spender)?;
 
-    token.approvals = token
-        .approvals
-        .into_iter()
-        .filter(|apr| apr.spender != spender_raw)
-        .collect();
+    token.approvals.retain(|apr| apr.spender != spender_raw);
 
     // only difference between approve and revoke
     if add {
         // reject expired data as invalid
         let expires = expires.unwrap_or_default();
         if expires.is_expired(&env.block) {
-            return Err(ContractError::Std(StdError::generic_err(
-                "Cannot set approval that is already expired",
-            )));
+            return Err(ContractError::AlreadyExpired {});
         };
         let approval = Approval {
             spender: spender_raw,
@@ -257,7 +574,7 @@ pub fn _update_approvals(
         token.approvals.push(approval);
     }
 
-    JNS.save(deps.storage, &token_id, &token)?;
+    jns().save(deps.storage, &token_id, &token)?;
 
     Ok(NameResponse { name: token })
 }
@@ -268,22 +585,27 @@ pub fn try_send_nft (
     info: MessageInfo, 
     contract: String,
     token_id: String,
-    msg: Binary
+    msg: Binary,
+    keep_records: bool,
 ) -> Result<Response, ContractError> {
-    let token_id = token_id.to_lowercase();
-
-    // Unwrap message first
-    let _msgs: Vec<CosmosMsg> = vec![from_binary(&msg)?];
+    let token_id = normalize_full_name(&token_id)?;
 
     // Transfer token
-    let res = _try_transfer_nft(deps, env, info.clone(), contract.clone(), token_id.clone());
+    let res = _try_transfer_nft(deps, env, info.clone(), contract.clone(), token_id.clone(), keep_records)?;
 
-    if res.is_err() {
-        return Err(ContractError::Std(StdError::generic_err(res.unwrap_err().to_string())));
-    }
+    // Let the receiving contract know it got the name, the transfer is rolled back if it fails
+    let send = Cw721ReceiveMsg {
+        sender: info.sender.to_string(),
+        token_id: token_id.clone(),
+        msg,
+    };
 
-    // Send message
-    Ok(Response::new().add_attribute("action", "send_nft").add_attribute("sender", info.sender).add_attribute("recipient", contract).add_attribute("token_id", token_id))
+    Ok(res
+        .add_message(send.into_cosmos_msg(contract.clone())?)
+        .add_attribute("action", "send_nft")
+        .add_attribute("sender", info.sender)
+        .add_attribute("recipient", contract)
+        .add_attribute("token_id", token_id))
 
 }
 
@@ -292,14 +614,15 @@ pub fn transfer_nft (
     env: Env, 
     info: MessageInfo, 
     recipient: String,
-    token_id: String
+    token_id: String,
+    keep_records: bool,
 ) -> Result<Response, ContractError> {
-    let token_id = token_id.to_lowercase();
+    let token_id = normalize_full_name(&token_id)?;
 
-    _try_transfer_nft(deps, env, info, recipient.clone(), token_id.clone())?;
+    let res = _try_transfer_nft(deps, env, info, recipient.clone(), token_id.clone(), keep_records)?;
 
     Ok(
-        Response::new().add_attribute("method", "try_transfer_nft")
+        res.add_attribute("method", "try_transfer_nft")
         .add_attribute("name_transfered", token_id)
         .add_attribute("new_owner", recipient)
     )
@@ -331,10 +654,8 @@ fn check_can_send(
 
     // operator can send
     for o in op {
-        if &o.owner == sender_raw {
-            if !o.expires.is_expired(&env.block) {
-                return Ok(());
-            }
+        if &o.owner == sender_raw && !o.expires.is_expired(&env.block) {
+            return Ok(());
         }
     }
 
@@ -342,26 +663,29 @@ fn check_can_send(
     
 }
 
+/// Moves the name to `recipient`. Unless `keep_records` is set, the returned
+/// response lists the record keys and chains that were cleared.
 pub fn _try_transfer_nft (
     deps: DepsMut, 
     env: Env, 
     info: MessageInfo, 
     recipient: String,
-    token_id: String
+    token_id: String,
+    keep_records: bool,
 ) -> Result<Response, ContractError> {
-    let token_id = token_id.to_lowercase();
+    let token_id = normalize_full_name(&token_id)?;
 
     let store = deps.storage;
-    let existing_name = JNS.may_load(store, &token_id.clone())?;    // checks if the user is able to register the name
-    if existing_name == None {
-        return Err(ContractError::Std(StdError::not_found("Name not registered.")));
+    let existing_name = jns().may_load(store, &token_id.clone())?;    // checks if the user is able to register the name
+    if existing_name.is_none() {
+        return Err(ContractError::NameNotFound { name: token_id });
     }
 
     let real_name = existing_name.unwrap();
     let checked= deps.api.addr_validate(&recipient);
 
     let sender_raw = &info.sender;
-    let sraw = deps.api.addr_canonicalize(&sender_raw.to_string())?;
+    let sraw = deps.api.addr_canonicalize(sender_raw.as_str())?;
     let op = OPERATORS.may_load(store, real_name.owner.to_string())?;
 
     let r = check_can_send(op.unwrap_or(vec![]), sraw, sender_raw, &env, &info, real_name.clone());
@@ -371,103 +695,99 @@ pub fn _try_transfer_nft (
     }
 
     if checked.is_err() {
-        return Err(ContractError::Std(StdError::generic_err("Recipient is not a valid address.")));
+        return Err(ContractError::InvalidRecipient { recipient });
     }
 
     let address = checked.unwrap();
     
-    let new_name = Name {
-        id: real_name.id,
+    let mut new_name = Name {
+        id: real_name.id.clone(),
         expires: real_name.expires,
-        owner: address.clone(),
+        owner: address,
         approvals: vec![],
-        avatar_url: None,
-        terra_address: None,
-        secret_address: None,
-        crypto_org_address: None,
-        starname_address: None,
-        persistence_address: None,
-        kava_address: None,
-        website: None,
-        email: None,
-        twitter: None,
-        telegram: None,
-        discord: None,
-        instagram: None,
-        reddit: None
+        records: real_name.records,
+        addresses: real_name.addresses,
     };
 
-    JNS.save(store, &token_id.clone(), &new_name)?;
+    let mut res = Response::new().add_attribute("keep_records", keep_records.to_string());
+    if !keep_records {
+        // attribute values can't be empty, so only report what was actually there
+        let records_cleared: Vec<String> = new_name.records.drain(..).map(|r| r.key).collect();
+        if !records_cleared.is_empty() {
+            res = res.add_attribute("records_cleared", records_cleared.join(","));
+        }
+        let addresses_cleared: Vec<String> = new_name.addresses.drain(..).map(|a| a.chain).collect();
+        if !addresses_cleared.is_empty() {
+            res = res.add_attribute("addresses_cleared", addresses_cleared.join(","));
+        }
+    }
 
-    Ok(
-        Response::new().add_attribute("method", "try_transfer_nft")
-        .add_attribute("name_transfered", token_id)
-        .add_attribute("new_owner", address.to_string())
-    )
+    jns().save(store, &token_id.clone(), &new_name)?;
+    clear_primary_name(store, &real_name.owner, &token_id)?;
+
+    Ok(res)
 }
 
 
 pub fn try_add_time(
     deps: DepsMut, 
-    _env: Env, 
+    env: Env, 
     info: MessageInfo, 
     name: String, 
     years: u64
 )-> Result<Response, ContractError> {
     let store = deps.storage;
-    let name = name.to_lowercase();
+    let name = normalize_name(&name)?;
+    // paying the redemption fee for no time would leave the name in redemption
+    if years == 0 {
+        return Err(ContractError::NoYears {});
+    }
 
-    let existing_name = JNS.may_load(store, &name.clone())?;    // checks if the user is able to register the name
-    if existing_name == None {
-        return Err(ContractError::Unauthorized {});
+    let existing_name = jns().may_load(store, &name.clone())?;    // checks if the user is able to register the name
+    if existing_name.is_none() {
+        return Err(ContractError::NameNotFound { name });
     }
 
     let mut real_name = existing_name.unwrap();
 
-    if real_name.owner != info.sender {
+    let state = STATE.load(store)?;
+    let now = env.block.time.nanos();
+    let denom = payment_denom(&info, &state)?;
+    let cost_per_year = match yearly_price_in(store, &state, &name, &denom)? {
+        Some(cost) => cost,
+        None => return Err(ContractError::UnsupportedDenom { denom }),
+    };
+    let mut total_cost = total_price(cost_per_year, years)?;
+
+    // anyone can keep a live name going, once it lapsed only its owner can bring it back
+    // and past grace they pay to redeem, after that the name is up for grabs
+    let status = real_name.status(&state, now);
+    if status != NameStatus::Active && real_name.owner != info.sender {
         return Err(ContractError::Unauthorized {});
     }
-
-    let char_count = name.chars().count();
-
-    let state = STATE.load(store).unwrap();
-    let mut _cost = state.cost_for_6;
-
-    match char_count {
-        1 => {
-            _cost = state.cost_for_1;
-        },
-        2 => {
-            _cost = state.cost_for_2;
-        },
-        3 => {
-            _cost = state.cost_for_3;
-        },
-        4 => {
-            _cost = state.cost_for_4;
-        },
-        5 => {
-            _cost = state.cost_for_5;
-        },
-        _ => {
-            _cost = state.cost_for_6;
+    match status {
+        NameStatus::Active | NameStatus::Grace => {}
+        NameStatus::Redemption => {
+            // the fee is only set in the main denom
+            if denom != state.denom && !state.redemption_fee.is_zero() {
+                return Err(ContractError::UnsupportedDenom { denom });
+            }
+            total_cost = total_cost.checked_add(state.redemption_fee)?;
         }
+        NameStatus::Available => return Err(ContractError::Expired { name }),
     }
 
-    let total_cost = _cost * years;
+    real_name.expires = add_years(real_name.expires, years)?;
+    check_registration_horizon(&state, now, real_name.expires)?;
 
-    let funds = NativeBalance(info.funds);
-    let passes = funds.has(&Coin {denom: String::from(state.denom), amount: Uint128::from(total_cost)});
-    if !passes {
-        return Err(ContractError::Unauthorized {});
-    }
+    let refund = take_payment(&info, &denom, total_cost)?;
 
-    real_name.expires = real_name.expires + Timestamp::from_seconds(SECONDS_IN_YEAR * years).nanos();
-
-    JNS.save(store, &name.clone(), &real_name)?;
+    jns().save(store, &name.clone(), &real_name)?;
 
     Ok(
-        Response::new().add_attribute("method", "try_add_time")
+        Response::new().add_messages(refund).add_attribute("method", "try_add_time")
+        .add_attribute("renewed_by", info.sender)
+        .add_attribute("denom", denom)
         .add_attribute("tokens_used", total_cost.to_string())
         .add_attribute("name_extended", name)
         .add_attribute("data_accepted", real_name)
@@ -478,508 +798,3349 @@ pub fn try_update_name(
     env: Env, 
     info: MessageInfo, 
     name: String, 
-    avatar_url: Option<String>, 
-    terra_address: Option<String>, 
-    secret_address: Option<String>, 
-    crypto_org_address: Option<String>, 
-    starname_address: Option<String>, 
-    persistence_address: Option<String>, 
-    kava_address: Option<String>, 
-    website: Option<String>, 
-    email: Option<String>,
-    twitter: Option<String>, 
-    telegram: Option<String>, 
-    discord: Option<String>, 
-    instagram: Option<String>, 
-    reddit: Option<String>
+    records: Option<Vec<RecordUpdate>>, 
+    addresses: Option<Vec<AddressUpdate>>, 
 ) -> Result<Response, ContractError> {
-    let name = name.to_lowercase();
-
-    // load and save with extra key argument
-    let store = deps.storage;
-
-    let current_time = env.block.time.nanos();
-    let existing_name = JNS.may_load(store, &name.clone());    // checks if the user is able to register the name
-    if existing_name.is_err() {
-        return Err(ContractError::Std(StdError::not_found("Name not register.")));
-    }
-
-    let existing_name = existing_name.unwrap().unwrap();
-
-    if existing_name.expires < current_time {
-        return Err(ContractError::Std(StdError::not_found("Name not register.")));
+    let name = normalize_full_name(&name)?;
+    let mut data = load_owned_name(deps.storage, &env, &info, &name)?;
+
+    // only the entries listed change, everything else including approvals is kept
+    for update in records.unwrap_or_default() {
+        match update.value {
+            Some(value) => {
+                validate_record(&update.key, &value)?;
+                data.set_record(update.key, value);
+            }
+            None => {
+                data.remove_record(&update.key);
+            }
+        }
     }
 
-    if existing_name.owner != info.sender {
-        return Err(ContractError::Unauthorized {});
+    for update in addresses.unwrap_or_default() {
+        match update.address {
+            Some(address) => {
+                let entry = validate_address(update.chain, address)?;
+                data.set_address(entry.chain, entry.address);
+            }
+            None => {
+                data.remove_address(&update.chain);
+            }
+        }
     }
 
+    check_entry_limits(&data)?;
 
-
-
-    let data = Name { 
-        id: existing_name.id, 
-        expires: existing_name.expires, 
-        owner: existing_name.owner, 
-        approvals: vec![],
-        avatar_url: avatar_url, 
-        terra_address: terra_address,
-        secret_address: secret_address,
-        crypto_org_address: crypto_org_address,
-        starname_address: starname_address,
-        persistence_address: persistence_address,
-        kava_address: kava_address,
-        website: website, 
-        email: email, 
-        twitter: twitter, 
-        telegram: telegram, 
-        discord: discord, 
-        instagram: instagram, 
-        reddit: reddit 
-    };
-
-    
-
-    JNS.save(store, &name.clone(), &data)?;
+    jns().save(deps.storage, &name, &data)?;
 
     Ok(
-        Response::new().add_attribute("method", "try_register_name")
+        Response::new().add_attribute("method", "try_update_name")
         .add_attribute("name_updated", name)
         .add_attribute("data_accepted", data)
     )
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn try_register_name(
     deps: DepsMut, 
     env: Env, 
     info: MessageInfo, 
     name: String, 
     years: u64, 
-    avatar_url: Option<String>, 
-    terra_address: Option<String>, 
-    secret_address: Option<String>, 
-    crypto_org_address: Option<String>, 
-    starname_address: Option<String>, 
-    persistence_address: Option<String>, 
-    kava_address: Option<String>, 
-    website: Option<String>, 
-    email: Option<String>,
-    twitter: Option<String>, 
-    telegram: Option<String>, 
-    discord: Option<String>, 
-    instagram: Option<String>, 
-    reddit: Option<String>
+    records: Option<Vec<TextRecord>>, 
+    addresses: Option<Vec<ChainAddress>>, 
 ) -> Result<Response, ContractError> {
 
     // load and save with extra key argument
     let store = deps.storage;
 
-    let char_count = name.chars().count();
-
-
-    let state = STATE.load(store).unwrap();
+    let state = STATE.load(store)?;
 
     let current_time = env.block.time.nanos();
 
-    let name = name.to_lowercase();
-
-    let existing_name = JNS.may_load(store, &name.clone())?;    // checks if the user is able to register the name
-    match existing_name {
-        Some(x) => {
-            if x.expires > current_time {
-                return Err(ContractError::Std(StdError::generic_err("Name is already registered.")));
-            }
-        }
-        None => {}
+    let name = normalize_name(&name)?;
+    if years == 0 {
+        return Err(ContractError::NoYears {});
     }
+    let records = validate_records(records.unwrap_or_default())?;
+    let addresses = validate_addresses(addresses.unwrap_or_default())?;
 
-    let mut _cost = state.cost_for_6;
+    if RESERVED.has(store, &name) {
+        return Err(ContractError::NameReserved { name });
+    }
+    if AUCTIONS.has(store, &name) {
+        return Err(ContractError::NameInAuction { name });
+    }
 
-    match char_count {
-        1 => {
-            _cost = state.cost_for_1;
-        },
-        2 => {
-            _cost = state.cost_for_2;
-        },
-        3 => {
-            _cost = state.cost_for_3;
-        },
-        4 => {
-            _cost = state.cost_for_4;
-        },
-        5 => {
-            _cost = state.cost_for_5;
-        },
-        _ => {
-            _cost = state.cost_for_6;
-        }
+    let mut premium = Uint128::zero();
+    let existing_name = jns().may_load(store, &name.clone())?;    // checks if the user is able to register the name
+    if let Some(x) = existing_name {
+        check_available(&x, &state, current_time)?;
+
+        // the name expired, so it no longer resolves back to the previous owner
+        // and the new owner decides on subdomain sales
+        clear_primary_name(store, &x.owner, &name)?;
+        SUBDOMAIN_PRICES.remove(store, &name);
+        premium = expiry_premium(&state, x.redemption_ends(&state), current_time);
+    } else {
+        // re-registering an expired name reuses its token, only new names add one
+        increment_tokens(store)?;
     }
 
-    let total_cost = _cost * years;
+    let expiration_date = add_years(current_time, years)?; // creates the name data
+    check_registration_horizon(&state, current_time, expiration_date)?;
 
-    let funds = NativeBalance(info.funds);
-    let passes = funds.has(&Coin {denom: String::from(state.denom), amount: Uint128::from(total_cost)});
+    // the premium is only set in the main denom
+    let denom = payment_denom(&info, &state)?;
+    let cost_per_year = match yearly_price_in(store, &state, &name, &denom)? {
+        Some(cost) if denom == state.denom || premium.is_zero() => cost,
+        _ => return Err(ContractError::UnsupportedDenom { denom }),
+    };
+    let total_cost = total_price(cost_per_year, years)?.checked_add(premium)?;
 
-    if !passes {
-        return Err(ContractError::Std(StdError::generic_err(format!("Not enough juno being sent. Wanted: {}", total_cost))));
-    }
+    let refund = take_payment(&info, &denom, total_cost)?;
 
-    
 
-    let expiration_date = current_time + ( Timestamp::from_seconds(SECONDS_IN_YEAR * years).nanos()) ; // creates the name data
     let data = Name { 
         id: name.clone(), 
         expires: expiration_date, 
         owner: info.sender, 
         approvals: vec![],
-        avatar_url: avatar_url, 
-        terra_address: terra_address,
-        secret_address: secret_address, 
-        crypto_org_address: crypto_org_address, 
-        starname_address: starname_address, 
-        persistence_address: persistence_address, 
-        kava_address: kava_address, 
-        website: website, 
-        email: email, 
-        twitter: twitter, 
-        telegram: telegram, 
-        discord: discord, 
-        instagram: instagram, 
-        reddit: reddit 
+        records, 
+        addresses, 
     };
 
     
 
-    JNS.save(store, &name.clone(), &data)?;
+    jns().save(store, &name.clone(), &data)?;
 
     Ok(
-        Response::new().add_attribute("method", "try_register_name")
+        Response::new().add_messages(refund).add_attribute("method", "try_register_name")
+        .add_attribute("denom", denom)
         .add_attribute("tokens_used", total_cost.to_string())
         .add_attribute("name_registered", name)
         .add_attribute("data_accepted", data)
     )
 }
 
-pub fn try_set_blocks_per_year(deps: DepsMut, info: MessageInfo, blocks_per_year: u64) -> Result<Response, ContractError> {
-    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
-        if info.sender != state.owner {
-            return Err(ContractError::Unauthorized {});
+/// Checks every record key and value, returning them sorted by key with
+/// later duplicates replacing earlier ones.
+fn validate_records(records: Vec<TextRecord>) -> Result<Vec<TextRecord>, ContractError> {
+    let mut validated: Vec<TextRecord> = Vec::with_capacity(records.len());
+    for record in records {
+        validate_record(&record.key, &record.value)?;
+        match validated.binary_search_by(|r| r.key.cmp(&record.key)) {
+            Ok(i) => validated[i] = record,
+            Err(i) => validated.insert(i, record),
         }
-        state.blocks_per_year = blocks_per_year;
-        Ok(state)
-    })?;
+    }
 
-    Ok(Response::new().add_attribute("method", "try_increment"))
-}
-pub fn try_set_owner(deps: DepsMut, info: MessageInfo, owner: Addr) -> Result<Response, ContractError> {
-    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
-        if info.sender != state.owner {
-            return Err(ContractError::Unauthorized {});
-        }
-        state.owner = owner;
-        Ok(state)
-    })?;
-    Ok(Response::new().add_attribute("method", "reset"))
+    if validated.len() > MAX_RECORDS {
+        return Err(ContractError::TooManyRecords { max: MAX_RECORDS });
+    }
+
+    Ok(validated)
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::GetBlocksPerYear {} => to_binary(&query_blocks_per_year(deps)?),
-        QueryMsg::GetOwner {} => to_binary(&query_owner(deps)?),
-        QueryMsg::ResolveName { name } => to_binary(&query_name_owner(deps, env, name)?),
-        QueryMsg::ResolveAttributes { name } => to_binary(&query_name_attributes(deps, env, name)?),
-        QueryMsg::OwnerOf { token_id } => to_binary(&query_name_owner(deps, env, token_id)?),
-        QueryMsg::ApprovedForAll {
-            owner,
-            start_after,
-            limit,
-        } => to_binary(&query_all_approvals(deps, owner, start_after, limit)?),
-        QueryMsg::NumTokens {} => to_binary(&query_num_tokens()?),
-        QueryMsg::ContractInfo {} => to_binary(&query_contract_info()?),
-        QueryMsg::NftInfo { token_id } => to_binary(&query_nft_info(deps, env, token_id)?),
+/// Keys are short lowercase identifiers like `url` or `com.twitter`.
+fn validate_record(key: &str, value: &str) -> Result<(), ContractError> {
+    let key_ok = !key.is_empty()
+        && key.len() <= MAX_RECORD_KEY_LENGTH
+        && key.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '.' || c == '-' || c == '_');
+    if !key_ok {
+        return Err(ContractError::InvalidRecord {
+            key: key.to_string(),
+            reason: format!("keys are up to {} lowercase letters, digits, '.', '-' or '_'", MAX_RECORD_KEY_LENGTH),
+        });
     }
-}
 
-fn query_nft_info( deps: Deps, env:Env, token_id: String ) -> StdResult<NftInfoResponse> {
+    if value.len() > MAX_RECORD_VALUE_LENGTH {
+        return Err(ContractError::InvalidRecord {
+            key: key.to_string(),
+            reason: format!("value is longer than {} bytes", MAX_RECORD_VALUE_LENGTH),
+        });
+    }
+
+    Ok(())
+}
 
-    let token_id = token_id.to_lowercase();
+/// Checks every address, returning them sorted by chain with later
+/// duplicates replacing earlier ones.
+fn validate_addresses(addresses: Vec<ChainAddress>) -> Result<Vec<ChainAddress>, ContractError> {
+    let mut validated: Vec<ChainAddress> = Vec::with_capacity(addresses.len());
+    for entry in addresses {
+        let entry = validate_address(entry.chain, entry.address)?;
+        match validated.binary_search_by(|a| a.chain.cmp(&entry.chain)) {
+            Ok(i) => validated[i] = entry,
+            Err(i) => validated.insert(i, entry),
+        }
+    }
 
-    let exists = JNS.may_load(deps.storage, &token_id);
-    if exists.is_err() {
-        return Err(StdError::NotFound { kind: "Name is not registered.".to_string()});
+    if validated.len() > MAX_ADDRESSES {
+        return Err(ContractError::TooManyAddresses { max: MAX_ADDRESSES });
     }
 
-    let ret_name = exists.unwrap().unwrap();
+    Ok(validated)
+}
 
-    if ret_name.expires <= env.block.time.nanos() {
-        return Err(StdError::NotFound { kind: "Name is not registered.".to_string()});
+/// The address has to decode as bech32 with the chain's prefix, it is stored lowercase.
+fn validate_address(chain: String, address: String) -> Result<ChainAddress, ContractError> {
+    let (prefix, _) = bech32::decode(&address)?;
+    if prefix != chain {
+        return Err(ContractError::InvalidAddress {
+            address,
+            reason: format!("expected prefix {:?}", chain),
+        });
     }
 
-    let state = STATE.load(deps.storage)?;
+    Ok(ChainAddress { chain: prefix, address: address.to_ascii_lowercase() })
+}
 
+fn check_entry_limits(name: &Name) -> Result<(), ContractError> {
+    if name.records.len() > MAX_RECORDS {
+        return Err(ContractError::TooManyRecords { max: MAX_RECORDS });
+    }
+    if name.addresses.len() > MAX_ADDRESSES {
+        return Err(ContractError::TooManyAddresses { max: MAX_ADDRESSES });
+    }
+    Ok(())
+}
 
-    Ok(NftInfoResponse {
-        name: format!("{}.rns", token_id.clone()),
-        description: "An IBC Name Resolver living on the JUNO network.".to_string(),
-        image: format!("{}/{}", state.meta_url, token_id),
-    })
-} 
+/// Loads a name the sender owns and that has not expired.
+fn load_owned_name(storage: &dyn Storage, env: &Env, info: &MessageInfo, name: &str) -> Result<Name, ContractError> {
+    let existing_name = match jns().may_load(storage, name)? {
+        Some(n) if n.expires > env.block.time.nanos() => n,
+        _ => return Err(ContractError::NameNotFound { name: name.to_string() }),
+    };
 
+    if existing_name.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
 
-fn query_contract_info() -> StdResult<ContractInfoResponse> {
-    Ok(ContractInfoResponse {
-        name: "JACKAL Name Service".to_string(),
-        symbol: "RNS".to_string(),
-    })
-} 
+    Ok(existing_name)
+}
 
-fn query_num_tokens() -> StdResult<NumTokensResponse> {
-    Ok(NumTokensResponse {tokens: 0})
-} 
+pub fn try_set_record(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    name: String,
+    key: String,
+    value: String,
+) -> Result<Response, ContractError> {
+    let name = normalize_full_name(&name)?;
+    let mut existing_name = load_owned_name(deps.storage, &env, &info, &name)?;
 
-fn query_all_approvals(
-    deps: Deps,
+    validate_record(&key, &value)?;
+    existing_name.set_record(key.clone(), value);
+    check_entry_limits(&existing_name)?;
+
+    jns().save(deps.storage, &name, &existing_name)?;
+
+    Ok(
+        Response::new().add_attribute("method", "try_set_record")
+        .add_attribute("name", name)
+        .add_attribute("key", key)
+    )
+}
+
+pub fn try_remove_record(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    name: String,
+    key: String,
+) -> Result<Response, ContractError> {
+    let name = normalize_full_name(&name)?;
+    let mut existing_name = load_owned_name(deps.storage, &env, &info, &name)?;
+
+    if !existing_name.remove_record(&key) {
+        return Err(ContractError::RecordNotFound { key });
+    }
+
+    jns().save(deps.storage, &name, &existing_name)?;
+
+    Ok(
+        Response::new().add_attribute("method", "try_remove_record")
+        .add_attribute("name", name)
+        .add_attribute("key", key)
+    )
+}
+
+pub fn try_create_subdomain(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    parent: String,
+    label: String,
     owner: String,
-    _start_after: Option<String>,
-    _limit: Option<u32>,
-) -> StdResult<ApprovedForAllResponse> {
-    
-    let ops = OPERATORS.load(deps.storage, owner).unwrap_or(vec![]);
+    expires: Option<u64>,
+) -> Result<Response, ContractError> {
+    let parent = normalize_full_name(&parent)?;
+    let label = normalize_name(&label)?;
+    let name = normalize_full_name(&format!("{}.{}", label, parent))?;
+
+    let parent_record = load_owned_name(deps.storage, &env, &info, &parent)?;
+    let owner = deps.api.addr_validate(&owner).map_err(|_| ContractError::InvalidRecipient { recipient: owner.clone() })?;
+
+    // a subdomain can never outlive its parent
+    let now = env.block.time.nanos();
+    let expires = expires.unwrap_or(parent_record.expires).min(parent_record.expires);
+    if expires <= now {
+        return Err(ContractError::AlreadyExpired {});
+    }
+
+    mint_subdomain(deps.storage, &name, &owner, expires, now)?;
+
+    Ok(
+        Response::new().add_attribute("method", "try_create_subdomain")
+        .add_attribute("name_registered", name)
+        .add_attribute("owner", owner)
+        .add_attribute("expires", expires.to_string())
+    )
+}
+
+/// Saves a fresh subdomain, replacing an expired one under the same name.
+fn mint_subdomain(storage: &mut dyn Storage, name: &str, owner: &Addr, expires: u64, now: u64) -> Result<Name, ContractError> {
+    match jns().may_load(storage, name)? {
+        Some(existing) if existing.expires > now => {
+            return Err(ContractError::NameTaken { name: name.to_string() });
+        }
+        Some(existing) => {
+            clear_primary_name(storage, &existing.owner, name)?;
+            SUBDOMAIN_PRICES.remove(storage, name);
+            SOLD_SUBDOMAINS.remove(storage, name);
+        }
+        None => {
+            increment_tokens(storage)?;
+        }
+    }
+
+    let data = Name {
+        id: name.to_string(),
+        expires,
+        owner: owner.clone(),
+        approvals: vec![],
+        records: vec![],
+        addresses: vec![],
+    };
+    jns().save(storage, name, &data)?;
+
+    Ok(data)
+}
+
+pub fn try_extend_subdomain(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    name: String,
+    expires: Option<u64>,
+) -> Result<Response, ContractError> {
+    let name = normalize_full_name(&name)?;
+    let parent = match parent_name(&name) {
+        Some(p) => p,
+        None => return Err(ContractError::NotSubdomain { name }),
+    };
+
+    let parent_record = load_owned_name(deps.storage, &env, &info, parent)?;
+    let mut existing = match jns().may_load(deps.storage, &name)? {
+        Some(n) => n,
+        None => return Err(ContractError::NameNotFound { name }),
+    };
+
+    // still never past the parent
+    let expires = expires.unwrap_or(parent_record.expires).min(parent_record.expires);
+    if expires <= env.block.time.nanos() {
+        return Err(ContractError::AlreadyExpired {});
+    }
+    existing.expires = existing.expires.max(expires);
+    jns().save(deps.storage, &name, &existing)?;
+
+    Ok(
+        Response::new().add_attribute("method", "try_extend_subdomain")
+        .add_attribute("name", name)
+        .add_attribute("expires", existing.expires.to_string())
+    )
+}
+
+pub fn try_set_subdomain_price(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    parent: String,
+    price: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let parent = normalize_full_name(&parent)?;
+    load_owned_name(deps.storage, &env, &info, &parent)?;
+
+    let res = Response::new().add_attribute("method", "try_set_subdomain_price")
+        .add_attribute("parent", parent.clone());
+
+    match price {
+        Some(price) => {
+            // pinned to today's denom, a later config change doesn't reprice the sale
+            let price = Coin { denom: STATE.load(deps.storage)?.denom, amount: price };
+            SUBDOMAIN_PRICES.save(deps.storage, &parent, &price)?;
+            Ok(res.add_attribute("price", price.to_string()))
+        }
+        None => {
+            SUBDOMAIN_PRICES.remove(deps.storage, &parent);
+            Ok(res.add_attribute("price", "none"))
+        }
+    }
+}
+
+pub fn try_buy_subdomain(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    parent: String,
+    label: String,
+) -> Result<Response, ContractError> {
+    let parent = normalize_full_name(&parent)?;
+    let label = normalize_name(&label)?;
+    let name = normalize_full_name(&format!("{}.{}", label, parent))?;
+
+    let state = STATE.load(deps.storage)?;
+    let now = env.block.time.nanos();
+
+    let parent_record = match jns().may_load(deps.storage, &parent)? {
+        Some(p) if p.expires > now => p,
+        _ => return Err(ContractError::NameNotFound { name: parent }),
+    };
+    let price = match SUBDOMAIN_PRICES.may_load(deps.storage, &parent)? {
+        Some(p) => p,
+        None => return Err(ContractError::NotForSale { parent }),
+    };
+
+    let refund = take_payment(&info, &price.denom, price.amount)?;
+
+    mint_subdomain(deps.storage, &name, &info.sender, parent_record.expires, now)?;
+    SOLD_SUBDOMAINS.save(deps.storage, &name, &Empty {})?;
+
+    let (protocol_fee, parent_share) = split_subdomain_price(price.amount, state.subdomain_fee_bps);
+    let mut res = Response::new().add_messages(refund);
+    if !parent_share.is_zero() {
+        res = res.add_message(BankMsg::Send {
+            to_address: parent_record.owner.to_string(),
+            amount: vec![Coin { denom: price.denom.clone(), amount: parent_share }],
+        });
+    }
+
+    Ok(
+        res.add_attribute("method", "try_buy_subdomain")
+        .add_attribute("name_registered", name)
+        .add_attribute("owner", info.sender)
+        .add_attribute("denom", price.denom)
+        .add_attribute("tokens_used", price.amount.to_string())
+        .add_attribute("protocol_fee", protocol_fee.to_string())
+        .add_attribute("parent_share", parent_share.to_string())
+    )
+}
+
+pub fn try_revoke_subdomain(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    name: String,
+) -> Result<Response, ContractError> {
+    let name = normalize_full_name(&name)?;
+    let parent = match parent_name(&name) {
+        Some(p) => p,
+        None => return Err(ContractError::NotSubdomain { name }),
+    };
+
+    load_owned_name(deps.storage, &env, &info, parent)?;
+    let existing = match jns().may_load(deps.storage, &name)? {
+        Some(n) => n,
+        None => return Err(ContractError::NameNotFound { name }),
+    };
+    // buyers paid for the whole term, wherever they sit in the tree
+    if let Some(sold) = find_sold_subdomain(deps.storage, &existing, env.block.time.nanos())? {
+        return Err(ContractError::SubdomainSold { name: sold });
+    }
+
+    let removed = remove_name_tree(deps.storage, &name)?;
+
+    Ok(
+        Response::new().add_attribute("method", "try_revoke_subdomain")
+        .add_attribute("name_revoked", name)
+        .add_attribute("names_removed", removed.to_string())
+    )
+}
+
+/// Finds a live subdomain sold with BuySubdomain among `name` and everything under it.
+fn find_sold_subdomain(storage: &dyn Storage, name: &Name, now: u64) -> StdResult<Option<String>> {
+    if name.expires > now && SOLD_SUBDOMAINS.has(storage, &name.id) {
+        return Ok(Some(name.id.clone()));
+    }
+
+    let children: Vec<(String, Name)> = jns()
+        .idx
+        .parent
+        .prefix(name.id.clone())
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<_>>()?;
+
+    for (_, child) in children.iter() {
+        if let Some(sold) = find_sold_subdomain(storage, child, now)? {
+            return Ok(Some(sold));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Removes `name` and all of its subdomains, returning how many names were removed.
+fn remove_name_tree(storage: &mut dyn Storage, name: &str) -> StdResult<u64> {
+    let children: Vec<String> = jns()
+        .idx
+        .parent
+        .prefix(name.to_string())
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<_>>()?;
+
+    let mut removed = 0;
+    for child in children.iter() {
+        removed += remove_name_tree(storage, child)?;
+    }
+
+    if let Some(existing) = jns().may_load(storage, name)? {
+        clear_primary_name(storage, &existing.owner, name)?;
+        SUBDOMAIN_PRICES.remove(storage, name);
+        SOLD_SUBDOMAINS.remove(storage, name);
+        jns().remove(storage, name)?;
+        decrement_tokens(storage)?;
+        removed += 1;
+    }
+
+    Ok(removed)
+}
+
+pub fn try_start_auction(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    name: String,
+    min_bid: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let name = normalize_name(&name)?;
+    let state = STATE.load(deps.storage)?;
+    let now = env.block.time.nanos();
+    let is_admin = info.sender == state.owner;
+
+    if AUCTIONS.has(deps.storage, &name) {
+        return Err(ContractError::NameInAuction { name });
+    }
+
+    // anyone can auction a name once its owner lost it, fresh names only by the admin
+    let mut premium = Uint128::zero();
+    match jns().may_load(deps.storage, &name)? {
+        Some(existing) => {
+            check_available(&existing, &state, now)?;
+            premium = expiry_premium(&state, existing.redemption_ends(&state), now);
+        }
+        None => {
+            if !is_admin {
+                return Err(ContractError::Unauthorized {});
+            }
+        }
+    }
+    if (min_bid.is_some() || RESERVED.has(deps.storage, &name)) && !is_admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // the same a registration would cost right now, so auctions don't skip the premium
+    let min_bid = match min_bid {
+        Some(bid) => bid,
+        None => yearly_price(deps.storage, &state, &name)?.checked_add(premium)?,
+    };
+    let auction = Auction {
+        name: name.clone(),
+        denom: state.denom.clone(),
+        min_bid,
+        highest_bid: Uint128::zero(),
+        highest_bidder: None,
+        ends: add_seconds(now, state.auction_duration)?,
+    };
+    AUCTIONS.save(deps.storage, &name, &auction)?;
+
+    Ok(
+        Response::new().add_attribute("method", "try_start_auction")
+        .add_attribute("name", name)
+        .add_attribute("min_bid", auction.min_bid.to_string())
+        .add_attribute("ends", auction.ends.to_string())
+    )
+}
+
+/// Smallest bid the auction accepts next.
+pub fn min_next_bid(auction: &Auction, increment_bps: u64) -> Result<Uint128, OverflowError> {
+    if auction.highest_bidder.is_none() {
+        return Ok(auction.min_bid);
+    }
+
+    let increment = auction.highest_bid.multiply_ratio(increment_bps, BPS_DENOMINATOR);
+    auction.highest_bid.checked_add(increment.max(Uint128::new(1)))
+}
+
+pub fn try_bid(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    name: String,
+) -> Result<Response, ContractError> {
+    let name = normalize_name(&name)?;
+    let state = STATE.load(deps.storage)?;
+
+    let mut auction = match AUCTIONS.may_load(deps.storage, &name)? {
+        Some(a) => a,
+        None => return Err(ContractError::AuctionNotFound { name }),
+    };
+    if env.block.time.nanos() >= auction.ends {
+        return Err(ContractError::AuctionEnded { name });
+    }
+
+    let bid = must_pay(&info, &auction.denom)?;
+    let required = min_next_bid(&auction, state.min_bid_increment_bps)?;
+    if bid < required {
+        return Err(ContractError::BidTooLow { required });
+    }
+
+    // hand the escrowed funds back to the bidder we just beat
+    let mut res = Response::new();
+    if let Some(previous) = auction.highest_bidder.take() {
+        res = res.add_message(BankMsg::Send {
+            to_address: previous.to_string(),
+            amount: vec![Coin { denom: auction.denom.clone(), amount: auction.highest_bid }],
+        });
+    }
+
+    auction.highest_bid = bid;
+    auction.highest_bidder = Some(info.sender.clone());
+    AUCTIONS.save(deps.storage, &name, &auction)?;
+
+    Ok(
+        res.add_attribute("method", "try_bid")
+        .add_attribute("name", name)
+        .add_attribute("bidder", info.sender)
+        .add_attribute("bid", bid.to_string())
+    )
+}
+
+pub fn try_close_auction(
+    deps: DepsMut,
+    env: Env,
+    name: String,
+) -> Result<Response, ContractError> {
+    let name = normalize_name(&name)?;
+    let now = env.block.time.nanos();
+
+    let auction = match AUCTIONS.may_load(deps.storage, &name)? {
+        Some(a) => a,
+        None => return Err(ContractError::AuctionNotFound { name }),
+    };
+    if now < auction.ends {
+        return Err(ContractError::AuctionRunning { name });
+    }
+    AUCTIONS.remove(deps.storage, &name);
+
+    let res = Response::new().add_attribute("method", "try_close_auction")
+        .add_attribute("name", name.clone());
+
+    // without bids the name simply stays available
+    let winner = match auction.highest_bidder {
+        Some(w) => w,
+        None => return Ok(res.add_attribute("winner", "none")),
+    };
+
+    let expires = add_years(now, 1)?;
+    assign_name(deps.storage, &name, &winner, expires)?;
+
+    Ok(
+        res.add_attribute("winner", winner)
+        .add_attribute("tokens_used", auction.highest_bid.to_string())
+    )
+}
+
+/// Hands a top level name to `owner` outside of the paid registration, taking
+/// over the token of a previous registration.
+fn assign_name(storage: &mut dyn Storage, name: &str, owner: &Addr, expires: u64) -> StdResult<Name> {
+    match jns().may_load(storage, name)? {
+        Some(previous) => {
+            clear_primary_name(storage, &previous.owner, name)?;
+            SUBDOMAIN_PRICES.remove(storage, name);
+        }
+        None => {
+            increment_tokens(storage)?;
+        }
+    }
+
+    let data = Name {
+        id: name.to_string(),
+        expires,
+        owner: owner.clone(),
+        approvals: vec![],
+        records: vec![],
+        addresses: vec![],
+    };
+    jns().save(storage, name, &data)?;
+
+    Ok(data)
+}
+
+pub fn try_add_reserved(
+    deps: DepsMut,
+    info: MessageInfo,
+    names: Vec<String>,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    for name in names.iter() {
+        RESERVED.save(deps.storage, &normalize_name(name)?, &Empty {})?;
+    }
+
+    Ok(
+        Response::new().add_attribute("method", "try_add_reserved")
+        .add_attribute("names_reserved", names.len().to_string())
+    )
+}
+
+pub fn try_remove_reserved(
+    deps: DepsMut,
+    info: MessageInfo,
+    names: Vec<String>,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    for name in names.iter() {
+        RESERVED.remove(deps.storage, &normalize_name(name)?);
+    }
+
+    Ok(
+        Response::new().add_attribute("method", "try_remove_reserved")
+        .add_attribute("names_released", names.len().to_string())
+    )
+}
+
+pub fn try_mint_reserved(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    name: String,
+    owner: String,
+    years: u64,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let name = normalize_name(&name)?;
+    if years == 0 {
+        return Err(ContractError::NoYears {});
+    }
+    if !RESERVED.has(deps.storage, &name) {
+        return Err(ContractError::NotReserved { name });
+    }
+    if AUCTIONS.has(deps.storage, &name) {
+        return Err(ContractError::NameInAuction { name });
+    }
+    let owner = deps.api.addr_validate(&owner).map_err(|_| ContractError::InvalidRecipient { recipient: owner.clone() })?;
+
+    let now = env.block.time.nanos();
+    if let Some(existing) = jns().may_load(deps.storage, &name)? {
+        check_available(&existing, &state, now)?;
+    }
+
+    let expires = add_years(now, years)?;
+    check_registration_horizon(&state, now, expires)?;
+    assign_name(deps.storage, &name, &owner, expires)?;
+
+    Ok(
+        Response::new().add_attribute("method", "try_mint_reserved")
+        .add_attribute("name_registered", name)
+        .add_attribute("owner", owner)
+    )
+}
+
+pub fn try_release_quarantined(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+    name: Option<String>,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let legacy = match migrations::QUARANTINED.may_load(deps.storage, &id)? {
+        Some(n) => n,
+        None => return Err(ContractError::NameNotFound { name: id }),
+    };
+
+    let res = Response::new().add_attribute("method", "try_release_quarantined")
+        .add_attribute("id", format!("{:?}", id))
+        .add_attribute("owner", legacy.owner.clone());
+
+    let name = match name {
+        Some(name) => normalize_name(&name)?,
+        None => {
+            migrations::QUARANTINED.remove(deps.storage, &id);
+            return Ok(res.add_attribute("name_registered", "none"));
+        }
+    };
+
+    // the new name goes through the same checks as any registration
+    let now = env.block.time.nanos();
+    if legacy.expires <= now {
+        return Err(ContractError::AlreadyExpired {});
+    }
+    if AUCTIONS.has(deps.storage, &name) {
+        return Err(ContractError::NameInAuction { name });
+    }
+    if let Some(existing) = jns().may_load(deps.storage, &name)? {
+        check_available(&existing, &state, now)?;
+    }
+
+    migrations::QUARANTINED.remove(deps.storage, &id);
+    let mut data = assign_name(deps.storage, &name, &legacy.owner, legacy.expires)?;
+    data.records = legacy.records;
+    data.addresses = legacy.addresses;
+    jns().save(deps.storage, &name, &data)?;
+
+    Ok(res.add_attribute("name_registered", name))
+}
+
+pub fn try_set_price_overrides(
+    deps: DepsMut,
+    info: MessageInfo,
+    prices: Vec<NamePrice>,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    for price in prices.iter() {
+        PRICE_OVERRIDES.save(deps.storage, &normalize_name(&price.name)?, &price.cost)?;
+    }
+
+    Ok(
+        Response::new().add_attribute("method", "try_set_price_overrides")
+        .add_attribute("names_priced", prices.len().to_string())
+    )
+}
+
+pub fn try_remove_price_overrides(
+    deps: DepsMut,
+    info: MessageInfo,
+    names: Vec<String>,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    for name in names.iter() {
+        PRICE_OVERRIDES.remove(deps.storage, &normalize_name(name)?);
+    }
+
+    Ok(
+        Response::new().add_attribute("method", "try_remove_price_overrides")
+        .add_attribute("names_unpriced", names.len().to_string())
+    )
+}
+
+/// Hex encoded sha256 of `"{name}:{owner}:{secret}"`, binding the commitment to its sender.
+pub fn make_commitment(name: &str, owner: &Addr, secret: &str) -> String {
+    let hash = Sha256::digest(format!("{}:{}:{}", name, owner, secret).as_bytes());
+    hash.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn try_commit_name(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    commitment: String,
+) -> Result<Response, ContractError> {
+    let commitment = commitment.to_lowercase();
+    if commitment.len() != 64 || !commitment.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(ContractError::InvalidCommitment {});
+    }
+
+    let state = STATE.load(deps.storage)?;
+    let now = env.block.time.nanos();
+
+    // a live commitment cannot be refreshed, that would let it dodge the minimum age
+    if let Some(existing) = commitments().may_load(deps.storage, &commitment)? {
+        if now <= add_seconds(existing.time, state.max_commitment_age)? {
+            return Err(ContractError::CommitmentExists {});
+        }
+    }
+
+    // commitments that were never revealed would pile up, each new one clears a few
+    let pruned = prune_commitments(deps.storage, &state, now)?;
+
+    commitments().save(deps.storage, &commitment, &Commitment { owner: info.sender.clone(), time: now })?;
+
+    Ok(
+        Response::new().add_attribute("method", "try_commit_name")
+        .add_attribute("owner", info.sender)
+        .add_attribute("commitment", commitment)
+        .add_attribute("commitments_pruned", pruned.to_string())
+    )
+}
+
+/// Removes up to `MAX_PRUNED_COMMITMENTS` of the oldest expired commitments,
+/// keeping the gas a single commit can spend on it bounded.
+fn prune_commitments(storage: &mut dyn Storage, state: &State, now: u64) -> Result<usize, ContractError> {
+    let oldest: Vec<(String, Commitment)> = commitments()
+        .idx
+        .time
+        .range(storage, None, None, Order::Ascending)
+        .take(MAX_PRUNED_COMMITMENTS)
+        .collect::<StdResult<_>>()?;
+
+    let mut pruned = 0;
+    for (key, commitment) in oldest {
+        if now <= add_seconds(commitment.time, state.max_commitment_age)? {
+            break;
+        }
+        commitments().remove(storage, &key)?;
+        pruned += 1;
+    }
+
+    Ok(pruned)
+}
+
+/// Consumes the sender's commitment to `name`, provided it is old enough and has not expired.
+fn check_commitment(
+    storage: &mut dyn Storage,
+    env: &Env,
+    info: &MessageInfo,
+    name: &str,
+    secret: &str,
+) -> Result<(), ContractError> {
+    let state = STATE.load(storage)?;
+    let commitment = make_commitment(&normalize_name(name)?, &info.sender, secret);
+
+    let existing = match commitments().may_load(storage, &commitment)? {
+        Some(c) if c.owner == info.sender => c,
+        _ => return Err(ContractError::CommitmentNotFound {}),
+    };
+
+    let now = env.block.time.nanos();
+    if now < add_seconds(existing.time, state.min_commitment_age)? {
+        return Err(ContractError::CommitmentTooNew {});
+    }
+    if now > add_seconds(existing.time, state.max_commitment_age)? {
+        return Err(ContractError::CommitmentExpired {});
+    }
+
+    commitments().remove(storage, &commitment)?;
+    Ok(())
+}
+
+pub fn try_set_primary_name(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    name: String,
+) -> Result<Response, ContractError> {
+    let name = normalize_full_name(&name)?;
+
+    let existing_name = jns().may_load(deps.storage, &name)?;
+    let existing_name = match existing_name {
+        Some(n) if n.expires > env.block.time.nanos() => n,
+        _ => return Err(ContractError::NameNotFound { name }),
+    };
+
+    if existing_name.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    PRIMARY_NAMES.save(deps.storage, &info.sender, &name)?;
+
+    Ok(
+        Response::new().add_attribute("method", "try_set_primary_name")
+        .add_attribute("owner", info.sender)
+        .add_attribute("primary_name", name)
+    )
+}
+
+/// Drops `owner`'s reverse record if it points at `name`.
+fn clear_primary_name(storage: &mut dyn Storage, owner: &Addr, name: &str) -> StdResult<()> {
+    if PRIMARY_NAMES.may_load(storage, owner)?.as_deref() == Some(name) {
+        PRIMARY_NAMES.remove(storage, owner);
+    }
+    Ok(())
+}
+
+pub fn try_set_blocks_per_year(deps: DepsMut, info: MessageInfo, blocks_per_year: u64) -> Result<Response, ContractError> {
+    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+        if info.sender != state.owner {
+            return Err(ContractError::Unauthorized {});
+        }
+        state.blocks_per_year = blocks_per_year;
+        Ok(state)
+    })?;
+
+    Ok(Response::new().add_attribute("method", "try_increment"))
+}
+pub fn try_set_owner(deps: DepsMut, info: MessageInfo, owner: Addr) -> Result<Response, ContractError> {
+    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+        if info.sender != state.owner {
+            return Err(ContractError::Unauthorized {});
+        }
+        state.owner = owner;
+        Ok(state)
+    })?;
+    Ok(Response::new().add_attribute("method", "reset"))
+}
+
+pub fn try_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    update: ConfigUpdate,
+) -> Result<Response, ContractError> {
+    let ConfigUpdate {
+        meta_url, denom, prices, grace_period, redemption_period, redemption_fee, require_commitment,
+        min_commitment_age, max_commitment_age, subdomain_fee_bps, auction_duration, min_bid_increment_bps,
+        premium, premium_days, premium_decay, max_registration_years, extra_denoms,
+    } = update;
+
+    let has_price_overrides = PRICE_OVERRIDES.keys(deps.storage, None, None, Order::Ascending).next().is_some();
+
+    let state = STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+        if info.sender != state.owner {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        if let Some(meta_url) = meta_url {
+            state.meta_url = meta_url;
+        }
+        if let Some(denom) = denom {
+            if denom.is_empty() {
+                return Err(ContractError::InvalidConfig { reason: String::from("denom cannot be empty") });
+            }
+            // amounts stored without a denom of their own would silently switch currency
+            if denom != state.denom {
+                if has_price_overrides {
+                    return Err(ContractError::InvalidConfig { reason: String::from("remove the price overrides before changing denom") });
+                }
+                if premium.is_none() && !state.premium.is_zero() {
+                    return Err(ContractError::InvalidConfig { reason: String::from("set the premium again when changing denom") });
+                }
+                if redemption_fee.is_none() && !state.redemption_fee.is_zero() {
+                    return Err(ContractError::InvalidConfig { reason: String::from("set the redemption fee again when changing denom") });
+                }
+            }
+            state.denom = denom;
+        }
+
+        if let Some(prices) = prices {
+            validate_prices(&prices)?;
+            state.prices = prices;
+        }
+
+        state.grace_period = grace_period.unwrap_or(state.grace_period);
+        state.redemption_period = redemption_period.unwrap_or(state.redemption_period);
+        state.redemption_fee = redemption_fee.unwrap_or(state.redemption_fee);
+        state.require_commitment = require_commitment.unwrap_or(state.require_commitment);
+        state.min_commitment_age = min_commitment_age.unwrap_or(state.min_commitment_age);
+        state.max_commitment_age = max_commitment_age.unwrap_or(state.max_commitment_age);
+        validate_commitment_ages(state.min_commitment_age, state.max_commitment_age)?;
+
+        if let Some(fee) = subdomain_fee_bps {
+            validate_fee_bps(fee)?;
+            state.subdomain_fee_bps = fee;
+        }
+
+        state.auction_duration = auction_duration.unwrap_or(state.auction_duration);
+        state.min_bid_increment_bps = min_bid_increment_bps.unwrap_or(state.min_bid_increment_bps);
+        state.premium = premium.unwrap_or(state.premium);
+        state.premium_days = premium_days.unwrap_or(state.premium_days);
+        state.premium_decay = premium_decay.unwrap_or(state.premium_decay);
+
+        if let Some(years) = max_registration_years {
+            validate_max_registration_years(years)?;
+            state.max_registration_years = years;
+        }
+
+        if let Some(extra_denoms) = extra_denoms {
+            state.extra_denoms = extra_denoms;
+        }
+        // checked even when only `denom` changed, it may now clash with an extra one
+        validate_extra_denoms(&state.denom, &state.extra_denoms)?;
+        Ok(state)
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_update_config")
+        .add_attribute("meta_url", state.meta_url)
+        .add_attribute("denom", state.denom))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetBlocksPerYear {} => to_binary(&query_blocks_per_year(deps)?),
+        QueryMsg::GetOwner {} => to_binary(&query_owner(deps)?),
+        QueryMsg::GetConfig {} => to_binary(&query_config(deps)?),
+        QueryMsg::PriceQuote { name, years } => to_binary(&query_price_quote(deps, name, years)?),
+        QueryMsg::RegistrationQuote { name, years } => to_binary(&query_registration_quote(deps, env, name, years)?),
+        QueryMsg::ResolveName { name } => to_binary(&query_name_owner(deps, env, name)?),
+        QueryMsg::ResolveAttributes { name } => to_binary(&query_name_attributes(deps, env, name)?),
+        QueryMsg::GetRecords { name, keys } => to_binary(&query_records(deps, env, name, keys)?),
+        QueryMsg::ResolveAddress { name, chain } => to_binary(&query_resolve_address(deps, env, name, chain)?),
+        QueryMsg::Subdomains { parent, start_after, limit } => to_binary(&query_subdomains(deps, parent, start_after, limit)?),
+        QueryMsg::SubdomainPriceQuote { parent } => to_binary(&query_subdomain_price(deps, env, parent)?),
+        QueryMsg::Auction { name } => to_binary(&query_auction(deps, name)?),
+        QueryMsg::Auctions { start_after, limit } => to_binary(&query_auctions(deps, start_after, limit)?),
+        QueryMsg::Reserved { start_after, limit } => to_binary(&query_reserved(deps, start_after, limit)?),
+        QueryMsg::Quarantined { start_after, limit } => to_binary(&query_quarantined(deps, start_after, limit)?),
+        QueryMsg::PriceOverrides { start_after, limit } => to_binary(&query_price_overrides(deps, start_after, limit)?),
+        QueryMsg::NameStatus { name } => to_binary(&query_name_status(deps, env, name)?),
+        QueryMsg::ReverseResolve { address } => to_binary(&query_reverse_resolve(deps, env, address)?),
+        QueryMsg::OwnerOf { token_id } => to_binary(&query_name_owner(deps, env, token_id)?),
+        QueryMsg::ApprovedForAll {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_all_approvals(deps, owner, start_after, limit)?),
+        QueryMsg::NumTokens {} => to_binary(&query_num_tokens(deps)?),
+        QueryMsg::Tokens {
+            owner,
+            start_after,
+            limit,
+            include_expired,
+        } => to_binary(&query_tokens(deps, env, owner, start_after, limit, include_expired.unwrap_or(false))?),
+        QueryMsg::AllTokens { start_after, limit } => to_binary(&query_all_tokens(deps, start_after, limit)?),
+        QueryMsg::ContractInfo {} => to_binary(&query_contract_info()?),
+        QueryMsg::NftInfo { token_id } => to_binary(&query_nft_info(deps, env, token_id)?),
+    }
+}
+
+/// Queries can only fail with a `StdError`.
+fn normalize_query_name(name: &str) -> StdResult<String> {
+    normalize_full_name(name).map_err(|e| StdError::generic_err(e.to_string()))
+}
+
+fn query_nft_info( deps: Deps, env:Env, token_id: String ) -> StdResult<NftInfoResponse> {
+
+    let token_id = normalize_query_name(&token_id)?;
+
+    resolve_active_name(deps, &env, &token_id)?;
+
+    let state = STATE.load(deps.storage)?;
+
+
+    Ok(NftInfoResponse {
+        name: format!("{}.rns", token_id.clone()),
+        description: "An IBC Name Resolver living on the JUNO network.".to_string(),
+        image: format!("{}/{}", state.meta_url, token_id),
+    })
+} 
+
+
+fn query_contract_info() -> StdResult<ContractInfoResponse> {
+    Ok(ContractInfoResponse {
+        name: "JACKAL Name Service".to_string(),
+        symbol: "RNS".to_string(),
+    })
+} 
+
+fn query_num_tokens(deps: Deps) -> StdResult<NumTokensResponse> {
+    Ok(NumTokensResponse {tokens: num_tokens(deps.storage)?})
+} 
+
+fn query_all_tokens(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<TokensResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.to_lowercase().into_bytes()));
+
+    let tokens = jns()
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(TokensResponse { tokens })
+}
+
+fn query_tokens(
+    deps: Deps,
+    env: Env,
+    owner: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    include_expired: bool,
+) -> StdResult<TokensResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|s| Bound::exclusive(s.to_lowercase()));
+    let now = env.block.time.nanos();
+
+    let tokens = jns()
+        .idx
+        .owner
+        .prefix(owner)
+        .range(deps.storage, start, None, Order::Ascending)
+        .filter(|item| match item {
+            Ok((_, name)) => include_expired || name.expires > now,
+            Err(_) => true,
+        })
+        .take(limit)
+        .map(|item| item.map(|(token_id, _)| token_id))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(TokensResponse { tokens })
+}
+
+fn query_all_approvals(
+    deps: Deps,
+    owner: String,
+    _start_after: Option<String>,
+    _limit: Option<u32>,
+) -> StdResult<ApprovedForAllResponse> {
+    
+    let ops = OPERATORS.load(deps.storage, owner).unwrap_or(vec![]);
+
+    Ok(ApprovedForAllResponse {operators: ops})
+
+}
+
+fn query_blocks_per_year(deps: Deps) -> StdResult<BlocksResponse> {
+    let state = STATE.load(deps.storage)?;
+    Ok(BlocksResponse { blocks_per_year: state.blocks_per_year })
+}
+
+fn query_config(deps: Deps) -> StdResult<State> {
+    STATE.load(deps.storage)
+}
+
+fn query_price_quote(deps: Deps, name: String, years: u64) -> StdResult<PriceResponse> {
+    // only top level names are bought from the contract
+    let name = normalize_name(&name).map_err(|e| StdError::generic_err(e.to_string()))?;
+    let state = STATE.load(deps.storage)?;
+
+    let cost_per_year = yearly_price(deps.storage, &state, &name)?;
+    let other_denoms = extra_denom_quotes(deps.storage, &state, &name, years)?;
+    let main_denom_only = main_denom_only(deps.storage, &state, &name, Uint128::zero())?;
+
+    Ok(PriceResponse {
+        name,
+        years,
+        denom: state.denom,
+        cost_per_year,
+        total: total_price(cost_per_year, years)?,
+        other_denoms,
+        main_denom_only,
+    })
+}
+
+fn query_registration_quote(deps: Deps, env: Env, name: String, years: u64) -> StdResult<RegistrationQuoteResponse> {
+    let name = normalize_name(&name).map_err(|e| StdError::generic_err(e.to_string()))?;
+    let state = STATE.load(deps.storage)?;
+
+    let base = total_price(yearly_price(deps.storage, &state, &name)?, years)?;
+    let premium = match jns().may_load(deps.storage, &name)? {
+        Some(existing) => expiry_premium(&state, existing.redemption_ends(&state), env.block.time.nanos()),
+        None => Uint128::zero(),
+    };
+    let main_denom_only = main_denom_only(deps.storage, &state, &name, premium)?;
+    let other_denoms = if main_denom_only.is_none() {
+        extra_denom_quotes(deps.storage, &state, &name, years)?
+    } else {
+        vec![]
+    };
+
+    Ok(RegistrationQuoteResponse {
+        name,
+        years,
+        denom: state.denom,
+        base,
+        premium,
+        total: base.checked_add(premium)?,
+        other_denoms,
+        main_denom_only,
+    })
+}
+
+fn query_owner(deps: Deps) -> StdResult<OwnerResponse> {
+    let state = STATE.load(deps.storage)?;
+    Ok(OwnerResponse { owner: state.owner })
+}
+
+fn query_name_attributes(deps: Deps, env: Env, name: String) -> StdResult<NameResponse> {
+    let name = normalize_query_name(&name)?;
+
+    let ret_name = resolve_active_name(deps, &env, &name)?;
+
+    Ok(NameResponse { name: ret_name })
+}
+
+fn query_records(deps: Deps, env: Env, name: String, keys: Option<Vec<String>>) -> StdResult<RecordsResponse> {
+    let name = normalize_query_name(&name)?;
+
+    let ret_name = resolve_active_name(deps, &env, &name)?;
+
+    let records = match keys {
+        Some(keys) => ret_name
+            .records
+            .into_iter()
+            .filter(|r| keys.contains(&r.key))
+            .collect(),
+        None => ret_name.records,
+    };
+
+    Ok(RecordsResponse { records })
+}
+
+fn query_resolve_address(deps: Deps, env: Env, name: String, chain: String) -> StdResult<AddressResponse> {
+    let name = normalize_query_name(&name)?;
+
+    let ret_name = resolve_active_name(deps, &env, &name)?;
+
+    match ret_name.address(&chain) {
+        Some(address) => Ok(AddressResponse { chain, address: address.to_string() }),
+        None => Err(StdError::not_found(format!("{} address of {}", chain, name))),
+    }
+}
+
+fn query_subdomains(
+    deps: Deps,
+    parent: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<TokensResponse> {
+    let parent = normalize_query_name(&parent)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|s| Bound::exclusive(s.to_lowercase()));
+
+    let tokens = jns()
+        .idx
+        .parent
+        .prefix(parent)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(TokensResponse { tokens })
+}
+
+fn query_subdomain_price(deps: Deps, env: Env, parent: String) -> StdResult<SubdomainPriceResponse> {
+    let parent = normalize_query_name(&parent)?;
+    resolve_active_name(deps, &env, &parent)?;
+
+    let state = STATE.load(deps.storage)?;
+    let price = match SUBDOMAIN_PRICES.may_load(deps.storage, &parent)? {
+        Some(p) => p,
+        None => return Err(StdError::generic_err(format!("Subdomains of {} are not for sale.", parent))),
+    };
+    let (protocol_fee, parent_share) = split_subdomain_price(price.amount, state.subdomain_fee_bps);
+
+    Ok(SubdomainPriceResponse {
+        parent,
+        denom: price.denom,
+        price: price.amount,
+        protocol_fee,
+        parent_share,
+    })
+}
+
+fn query_auction(deps: Deps, name: String) -> StdResult<Auction> {
+    let name = normalize_query_name(&name)?;
+    AUCTIONS.load(deps.storage, &name)
+}
+
+fn query_auctions(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AuctionsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.to_lowercase().into_bytes()));
+
+    let auctions = AUCTIONS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, auction)| auction))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(AuctionsResponse { auctions })
+}
+
+fn query_reserved(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ReservedResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.to_lowercase().into_bytes()));
+
+    let names = RESERVED
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(ReservedResponse { names })
+}
+
+fn query_quarantined(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<QuarantinedResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    // ids are raw legacy keys, so the cursor is taken as is
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into_bytes()));
+
+    let names = migrations::QUARANTINED
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, name)| name))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(QuarantinedResponse { names })
+}
+
+fn query_price_overrides(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<PriceOverridesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.to_lowercase().into_bytes()));
+
+    let prices = PRICE_OVERRIDES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(name, cost)| NamePrice { name, cost }))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(PriceOverridesResponse { prices })
+}
+
+fn query_name_status(deps: Deps, env: Env, name: String) -> StdResult<NameStatusResponse> {
+    let name = normalize_query_name(&name)?;
+    let state = STATE.load(deps.storage)?;
+
+    let res = match jns().may_load(deps.storage, &name)? {
+        Some(n) => NameStatusResponse {
+            status: n.status(&state, env.block.time.nanos()),
+            grace_ends: Some(n.grace_ends(&state)),
+            redemption_ends: Some(n.redemption_ends(&state)),
+            expires: Some(n.expires),
+            owner: Some(n.owner),
+            name,
+        },
+        None => NameStatusResponse {
+            name,
+            status: NameStatus::Available,
+            owner: None,
+            expires: None,
+            grace_ends: None,
+            redemption_ends: None,
+        },
+    };
+
+    Ok(res)
+}
+
+/// Loads a name that currently resolves, explaining where it is in its lifecycle otherwise.
+fn resolve_active_name(deps: Deps, env: &Env, name: &str) -> StdResult<Name> {
+    let found = match jns().may_load(deps.storage, name)? {
+        Some(n) => n,
+        None => return Err(StdError::NotFound { kind: "Name is not registered.".to_string()}),
+    };
+
+    let state = STATE.load(deps.storage)?;
+    match found.status(&state, env.block.time.nanos()) {
+        NameStatus::Active => {
+            // a subdomain only resolves while every name above it does
+            let mut ancestor = found.parent();
+            while let Some(parent) = ancestor {
+                let active = jns()
+                    .may_load(deps.storage, parent)?
+                    .map(|p| p.status(&state, env.block.time.nanos()) == NameStatus::Active)
+                    .unwrap_or(false);
+                if !active {
+                    return Err(StdError::generic_err(format!("Parent name {} is not active.", parent)));
+                }
+                ancestor = parent_name(parent);
+            }
+            Ok(found)
+        }
+        NameStatus::Grace => Err(StdError::generic_err(format!(
+            "Name expired and is in its grace period until {}.",
+            found.grace_ends(&state)
+        ))),
+        NameStatus::Redemption => Err(StdError::generic_err(format!(
+            "Name expired and is in its redemption period until {}.",
+            found.redemption_ends(&state)
+        ))),
+        NameStatus::Available => Err(StdError::generic_err("Name expired and is available for registration.")),
+    }
+}
+
+fn query_reverse_resolve(deps: Deps, env: Env, address: String) -> StdResult<ReverseResolveResponse> {
+    let address = deps.api.addr_validate(&address)?;
+
+    let primary = match PRIMARY_NAMES.may_load(deps.storage, &address)? {
+        Some(p) => p,
+        None => return Ok(ReverseResolveResponse { name: None }),
+    };
+
+    // only report the record while the address still holds a live name
+    let name = jns().may_load(deps.storage, &primary)?
+        .filter(|n| n.owner == address && n.expires > env.block.time.nanos())
+        .map(|n| n.id);
+
+    Ok(ReverseResolveResponse { name })
+}
+
+fn query_name_owner(deps: Deps, env: Env, name: String) -> StdResult<OwnerResponse> {
+    let name = normalize_query_name(&name)?;
+
+    let ret_name = resolve_active_name(deps, &env, &name)?;
+
+    Ok(OwnerResponse { owner: ret_name.owner })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_dependencies_with_balance, mock_env, mock_info, MOCK_CONTRACT_ADDR};
+    use cosmwasm_std::{coin, coins, from_binary, Timestamp};
+    use proptest::prelude::*;
+
+    
+
+    fn int_mgs() -> InstantiateMsg{
+
+        InstantiateMsg { 
+            blocks_per_year: 5048093, 
+            meta_url: "example.com".to_string(),
+            denom: "ujuno".to_string(),
+            prices: Some(vec![
+                PriceTier { min_length: 1, cost: Uint128::new(32) },
+                PriceTier { min_length: 2, cost: Uint128::new(16) },
+                PriceTier { min_length: 3, cost: Uint128::new(8) },
+                PriceTier { min_length: 4, cost: Uint128::new(4) },
+                PriceTier { min_length: 5, cost: Uint128::new(2) },
+                PriceTier { min_length: 6, cost: Uint128::new(1) },
+            ]),
+            grace_period: None,
+            redemption_period: None,
+            redemption_fee: None,
+            require_commitment: None,
+            min_commitment_age: None,
+            max_commitment_age: None,
+            subdomain_fee_bps: None,
+            auction_duration: None,
+            min_bid_increment_bps: None,
+            premium: None,
+            premium_days: None,
+            premium_decay: None,
+            max_registration_years: None,
+            extra_denoms: None,
+        }
+    }
+
+    #[test]
+    fn proper_initialization() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let msg = int_mgs();
+        let info = mock_info("creator", &coins(1000, "earth"));
+
+        // we can just call .unwrap() to assert this was a success
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // it worked, let's query the state
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetBlocksPerYear {}).unwrap();
+        let value: BlocksResponse = from_binary(&res).unwrap();
+        assert_eq!(5048093, value.blocks_per_year);
+    }
+
+    #[test]
+    fn change_block_count() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let msg = int_mgs();
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // beneficiary can release it
+        let info = mock_info("creator", &coins(2, "token"));
+        let msg = ExecuteMsg::SetBlocksPerYear { blocks_per_year: 5048094 };
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // should increase counter by 1
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetBlocksPerYear {}).unwrap();
+        let value: BlocksResponse = from_binary(&res).unwrap();
+        assert_eq!(5048094, value.blocks_per_year);
+    }
+
+    #[test]
+    fn change_owner() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let msg = int_mgs();
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // beneficiary can release it
+        let unauth_info = mock_info("anyone", &coins(2, "token"));
+        let msg = ExecuteMsg::SetOwner { owner: Addr::unchecked("anyone") };
+        let res = execute(deps.as_mut(), mock_env(), unauth_info, msg);
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+
+        // only the original creator can reset the counter
+        let auth_info = mock_info("creator", &coins(2, "token"));
+        let msg = ExecuteMsg::SetOwner { owner: Addr::unchecked("anyone") };
+        let _res = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+
+        // should now be 5
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetOwner {}).unwrap();
+        let value: OwnerResponse = from_binary(&res).unwrap();
+        assert_eq!(Addr::unchecked("anyone"), value.owner);
+    }
+
+    #[test]
+    fn update_config() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let msg = int_mgs();
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = ExecuteMsg::UpdateConfig(ConfigUpdate {
+            meta_url: Some("meta.example.com".to_string()),
+            prices: Some(vec![
+                PriceTier { min_length: 1, cost: Uint128::new(320) },
+                PriceTier { min_length: 5, cost: Uint128::new(20) },
+                PriceTier { min_length: 8, cost: Uint128::new(10) },
+            ]),
+            ..ConfigUpdate::default()
+        });
+
+        // only the owner may change the config
+        let unauth_info = mock_info("anyone", &[]);
+        let res = execute(deps.as_mut(), mock_env(), unauth_info, msg.clone());
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+
+        let auth_info = mock_info("creator", &[]);
+        let _res = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+
+        // the message still reads as a plain object of optional fields
+        let parsed: ExecuteMsg = from_binary(&Binary::from(br#"{"update_config":{"meta_url":"meta.example.com"}}"#.to_vec())).unwrap();
+        assert_eq!(ExecuteMsg::UpdateConfig(ConfigUpdate { meta_url: Some("meta.example.com".to_string()), ..ConfigUpdate::default() }), parsed);
+
+        // amounts kept without a denom have to be dealt with before the denom changes
+        let msg = ExecuteMsg::SetPriceOverrides { prices: vec![NamePrice { name: String::from("defi"), cost: Uint128::new(500) }] };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let msg = ExecuteMsg::UpdateConfig(ConfigUpdate { denom: Some(String::from("uatom")), ..ConfigUpdate::default() });
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg), Err(ContractError::InvalidConfig { .. })));
+        let msg = ExecuteMsg::RemovePriceOverrides { names: vec![String::from("defi")] };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::UpdateConfig(ConfigUpdate { redemption_fee: Some(Uint128::new(100)), ..ConfigUpdate::default() });
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let msg = ExecuteMsg::UpdateConfig(ConfigUpdate { denom: Some(String::from("uatom")), ..ConfigUpdate::default() });
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg), Err(ContractError::InvalidConfig { .. })));
+        let msg = ExecuteMsg::UpdateConfig(ConfigUpdate { redemption_fee: Some(Uint128::zero()), ..ConfigUpdate::default() });
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // fields left out are untouched
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap();
+        let value: State = from_binary(&res).unwrap();
+        assert_eq!("meta.example.com", value.meta_url);
+        assert_eq!("ujuno", value.denom);
+        assert_eq!(3, value.prices.len());
+        assert_eq!(PriceTier { min_length: 8, cost: Uint128::new(10) }, value.prices[2]);
+
+        // new prices apply to registrations
+        let auth_info = mock_info("annie", &coins(9, "ujuno"));
+        let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years: 1 , records: None, addresses: None};
+        let res = execute(deps.as_mut(), mock_env(), auth_info, msg);
+        assert!(matches!(res, Err(ContractError::InsufficientFunds { .. })));
+    }
+
+    #[test]
+    fn price_tiers() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let mut msg = int_mgs();
+        msg.prices = Some(vec![
+            PriceTier { min_length: 1, cost: Uint128::new(100) },
+            PriceTier { min_length: 2, cost: Uint128::new(50) },
+            PriceTier { min_length: 3, cost: Uint128::new(25) },
+            PriceTier { min_length: 4, cost: Uint128::new(12) },
+            PriceTier { min_length: 5, cost: Uint128::new(6) },
+            PriceTier { min_length: 8, cost: Uint128::new(3) },
+        ]);
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        for (name, cost) in [("a", 100), ("abcd", 12), ("abcde", 6), ("abcdefg", 6), ("abcdefgh", 3), ("abcdefghijkl", 3)] {
+            let res = query(deps.as_ref(), mock_env(), QueryMsg::PriceQuote { name: name.to_string(), years: 2 }).unwrap();
+            let value: PriceResponse = from_binary(&res).unwrap();
+            assert_eq!(Uint128::new(cost), value.cost_per_year);
+            assert_eq!(Uint128::new(cost * 2), value.total);
+            assert_eq!("ujuno", value.denom);
+        }
+
+        // register and renew charge the same schedule
+        let auth_info = mock_info("annie", &coins(5, "ujuno"));
+        let msg = ExecuteMsg::RegisterName { name: String::from("abcdef") , years: 1 , records: None, addresses: None};
+        assert!(matches!(execute(deps.as_mut(), mock_env(), auth_info, msg.clone()), Err(ContractError::InsufficientFunds { .. })));
+        let auth_info = mock_info("annie", &coins(6, "ujuno"));
+        execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+
+        let auth_info = mock_info("annie", &coins(11, "ujuno"));
+        let msg = ExecuteMsg::AddTime { name: String::from("abcdef") , years: 2 };
+        assert!(matches!(execute(deps.as_mut(), mock_env(), auth_info, msg.clone()), Err(ContractError::InsufficientFunds { .. })));
+        let auth_info = mock_info("annie", &coins(12, "ujuno"));
+        execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+    }
+
+    #[test]
+    fn invalid_price_tiers() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let mut msg = int_mgs();
+        msg.prices = Some(vec![PriceTier { min_length: 2, cost: Uint128::new(10) }]);
+        let info = mock_info("creator", &[]);
+        assert!(matches!(instantiate(deps.as_mut(), mock_env(), info, msg), Err(ContractError::InvalidConfig { .. })));
+
+        let mut msg = int_mgs();
+        msg.prices = Some(vec![]);
+        let info = mock_info("creator", &[]);
+        assert!(matches!(instantiate(deps.as_mut(), mock_env(), info, msg), Err(ContractError::InvalidConfig { .. })));
+
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, int_mgs()).unwrap();
+
+        let msg = ExecuteMsg::UpdateConfig(ConfigUpdate {
+            prices: Some(vec![
+                PriceTier { min_length: 1, cost: Uint128::new(10) },
+                PriceTier { min_length: 5, cost: Uint128::new(5) },
+                PriceTier { min_length: 5, cost: Uint128::new(1) },
+            ]),
+            ..ConfigUpdate::default()
+        });
+        let info = mock_info("creator", &[]);
+        assert!(matches!(execute(deps.as_mut(), mock_env(), info, msg), Err(ContractError::InvalidConfig { .. })));
+    }
+
+    #[test]
+    fn register_name() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let msg = int_mgs();
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+
+        // we can just call .unwrap() to assert this was a success
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        let auth_info = mock_info("annie", &coins(200000, "ujuno"));
+        let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years: 2 , records: None, addresses: None};
+        let res1 = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+
+        let auth_info = mock_info("bobby", &coins(200000, "ujuno"));
+        let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years: 3 , records: None, addresses: None};
+        let res2 = execute(deps.as_mut(), mock_env(), auth_info, msg);
+        
+        assert!(matches!(res2, Err(ContractError::NameTaken { .. })));
+
+        println!("{:?}", res1);
+        println!("{:?}", res2);
+    }
+
+    #[test]
+    fn add_time_to_name() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let msg = int_mgs();
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+
+        // we can just call .unwrap() to assert this was a success
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        let auth_info = mock_info("annie", &coins(200000, "ujuno"));
+        let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years: 2 , records: None, addresses: None};
+        let res1 = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+
+        let auth_info = mock_info("annie", &coins(200000, "ujuno"));
+        let msg = ExecuteMsg::AddTime { name: String::from("testname") , years: 2 };
+        let res2 = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+
+        // a friend can chip in, the name stays with its owner
+        let auth_info = mock_info("bobby", &coins(200000, "ujuno"));
+        let msg = ExecuteMsg::AddTime { name: String::from("testname") , years: 2 };
+        let res3 = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+        assert!(res3.attributes.iter().any(|a| a.key == "renewed_by" && a.value == "bobby"));
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ResolveAttributes { name: String::from("testname") }).unwrap();
+        let value: NameResponse = from_binary(&res).unwrap();
+        assert_eq!(Addr::unchecked("annie"), value.name.owner);
+        assert_eq!(mock_env().block.time.plus_seconds(SECONDS_IN_YEAR * 6).nanos(), value.name.expires);
+
+        // but nobody can push it more than ten years out
+        let auth_info = mock_info("bobby", &coins(200000, "ujuno"));
+        let msg = ExecuteMsg::AddTime { name: String::from("testname") , years: 5 };
+        assert!(matches!(execute(deps.as_mut(), mock_env(), auth_info, msg), Err(ContractError::TooFarAhead { .. })));
+        let auth_info = mock_info("annie", &coins(200000, "ujuno"));
+        let msg = ExecuteMsg::AddTime { name: String::from("testname") , years: 4 };
+        let _res = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+
+        // and renewing a missing name still fails
+        let auth_info = mock_info("bobby", &coins(200000, "ujuno"));
+        let msg = ExecuteMsg::AddTime { name: String::from("missing") , years: 1 };
+        assert!(matches!(execute(deps.as_mut(), mock_env(), auth_info, msg), Err(ContractError::NameNotFound { .. })));
+
+        println!("{:?}", res1);
+        println!("{:?}", res2);
+    }
+
+    #[test]
+    fn registration_horizon() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let mut msg = int_mgs();
+        msg.max_registration_years = Some(3);
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years: 4 , records: None, addresses: None};
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("annie", &coins(200000, "ujuno")), msg), Err(ContractError::TooFarAhead { .. })));
+        let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years: 3 , records: None, addresses: None};
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("annie", &coins(200000, "ujuno")), msg).unwrap();
+
+        // a year later there is room for exactly one more
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(SECONDS_IN_YEAR);
+        let msg = ExecuteMsg::AddTime { name: String::from("testname") , years: 2 };
+        assert!(matches!(execute(deps.as_mut(), env.clone(), mock_info("bobby", &coins(200000, "ujuno")), msg), Err(ContractError::TooFarAhead { .. })));
+        let msg = ExecuteMsg::AddTime { name: String::from("testname") , years: 1 };
+        let _res = execute(deps.as_mut(), env, mock_info("bobby", &coins(200000, "ujuno")), msg).unwrap();
+
+        let msg = ExecuteMsg::AddReserved { names: vec![String::from("jackal")] };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mint = ExecuteMsg::MintReserved { name: String::from("jackal"), owner: String::from("annie"), years: 4 };
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), mint), Err(ContractError::TooFarAhead { .. })));
+
+        let mut msg = int_mgs();
+        msg.max_registration_years = Some(0);
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+        assert!(matches!(instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg), Err(ContractError::InvalidConfig { .. })));
+    }
+
+    // edge values the date and price math gets checked against
+    const EXTREME_YEARS: [u64; 9] = [0, 1, 2, 10, 584, 585, u64::MAX / SECONDS_IN_YEAR, u64::MAX / 2, u64::MAX];
+    const EXTREME_TIMES: [u64; 5] = [0, 1, 1571797419879305533, u64::MAX - 1, u64::MAX];
+    const EXTREME_PRICES: [u128; 6] = [0, 1, 156250, u64::MAX as u128, u128::MAX / 2, u128::MAX];
+
+    fn check_date_math(time: u64, years: u64) {
+        let expected = u128::from(years) * u128::from(SECONDS_IN_YEAR) * u128::from(NANOS_IN_SECOND) + u128::from(time);
+        match add_years(time, years) {
+            Ok(expires) => assert_eq!(expected, u128::from(expires)),
+            Err(_) => assert!(expected > u128::from(u64::MAX), "{} + {} years must fit", time, years),
+        }
+
+        let expected = u128::from(years) * u128::from(NANOS_IN_SECOND) + u128::from(time);
+        match add_seconds(time, years) {
+            Ok(ends) => assert_eq!(expected, u128::from(ends)),
+            Err(_) => assert!(expected > u128::from(u64::MAX), "{} + {} seconds must fit", time, years),
+        }
+    }
+
+    fn check_price_math(price: u128, years: u64, fee_bps: u64) {
+        match total_price(Uint128::new(price), years) {
+            Ok(total) => assert_eq!(Some(total.u128()), price.checked_mul(u128::from(years))),
+            Err(_) => assert_eq!(None, price.checked_mul(u128::from(years))),
+        }
+
+        let (fee, share) = split_subdomain_price(Uint128::new(price), fee_bps);
+        assert_eq!(price, fee.u128() + share.u128());
+
+        let auction = Auction {
+            name: String::from("abc"),
+            denom: String::from("ujuno"),
+            min_bid: Uint128::new(1),
+            highest_bid: Uint128::new(price),
+            highest_bidder: Some(Addr::unchecked("annie")),
+            ends: 0,
+        };
+        match min_next_bid(&auction, DEFAULT_MIN_BID_INCREMENT_BPS) {
+            Ok(next) => assert!(next.u128() > price),
+            Err(_) => assert!(price > u128::MAX / 2),
+        }
+    }
+
+    #[test]
+    fn date_math_never_wraps() {
+        for &time in EXTREME_TIMES.iter() {
+            for &years in EXTREME_YEARS.iter() {
+                check_date_math(time, years);
+            }
+        }
+    }
+
+    #[test]
+    fn price_math_never_wraps() {
+        for &price in EXTREME_PRICES.iter() {
+            for &years in EXTREME_YEARS.iter() {
+                for &fee_bps in [0, 1, 500, 5_000, 9_999, 10_000].iter() {
+                    check_price_math(price, years, fee_bps);
+                }
+            }
+        }
+    }
+
+    // realistic terms next to arbitrary ones, which almost always overflow
+    fn any_years() -> impl Strategy<Value = u64> {
+        prop_oneof![0..1_000u64, any::<u64>()]
+    }
+
+    proptest! {
+        #[test]
+        fn date_math_never_wraps_for_any_input(time in any::<u64>(), years in any_years()) {
+            check_date_math(time, years);
+        }
+
+        #[test]
+        fn price_math_never_wraps_for_any_input(price in any::<u128>(), years in any_years(), fee_bps in 0..=BPS_DENOMINATOR) {
+            check_price_math(price, years, fee_bps);
+        }
+    }
+
+    #[test]
+    fn extreme_registrations() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        // no horizon, so only the overflow checks stand in the way
+        let mut msg = int_mgs();
+        msg.max_registration_years = Some(u64::MAX);
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        for &years in EXTREME_YEARS.iter().filter(|y| add_years(mock_env().block.time.nanos(), **y).is_err()) {
+            let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years , records: None, addresses: None};
+            match execute(deps.as_mut(), mock_env(), mock_info("annie", &coins(200000, "ujuno")), msg) {
+                Err(ContractError::Overflow(_)) => {}
+                res => panic!("{} years must overflow, got {:?}", years, res),
+            }
+        }
+
+        let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years: 500 , records: None, addresses: None};
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("annie", &coins(500, "ujuno")), msg).unwrap();
+        let msg = ExecuteMsg::AddTime { name: String::from("testname") , years: u64::MAX / SECONDS_IN_YEAR };
+        match execute(deps.as_mut(), mock_env(), mock_info("annie", &coins(200000, "ujuno")), msg) {
+            Err(ContractError::Overflow(_)) => {}
+            res => panic!("Must overflow, got {:?}", res),
+        }
+
+        // the most expensive name there can be still can't be bought for two years
+        let msg = ExecuteMsg::SetPriceOverrides { prices: vec![NamePrice { name: String::from("max"), cost: Uint128::MAX }] };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let msg = ExecuteMsg::RegisterName { name: String::from("max") , years: 2 , records: None, addresses: None};
+        match execute(deps.as_mut(), mock_env(), mock_info("annie", &coins(200000, "ujuno")), msg) {
+            Err(ContractError::Overflow(_)) => {}
+            res => panic!("Must overflow, got {:?}", res),
+        }
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::PriceQuote { name: String::from("max"), years: 1 }).unwrap();
+        let value: PriceResponse = from_binary(&res).unwrap();
+        assert_eq!(Uint128::MAX, value.total);
+        match query(deps.as_ref(), mock_env(), QueryMsg::PriceQuote { name: String::from("max"), years: 2 }) {
+            Err(StdError::Overflow { .. }) => {}
+            res => panic!("Must overflow, got {:?}", res),
+        }
+        assert!(query(deps.as_ref(), mock_env(), QueryMsg::RegistrationQuote { name: String::from("max"), years: u64::MAX }).is_err());
+    }
+
+    #[test]
+    fn resolve_name() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let msg = int_mgs();
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+
+        // we can just call .unwrap() to assert this was a success
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        let auth_info = mock_info("annie", &coins(200000, "ujuno"));
+        let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years: 2 , records: None, addresses: None};
+        let _res = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+
+        
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ResolveName { name : String::from("testname")}).unwrap();
+        let value: OwnerResponse = from_binary(&res).unwrap();
+        assert_eq!(Addr::unchecked("annie"), value.owner);
+
+    }
+
+    #[test]
+    fn request_name() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let msg = int_mgs();
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+
+        // we can just call .unwrap() to assert this was a success
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        let auth_info = mock_info("annie", &coins(200000, "ujuno"));
+        let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years: 2 , records: None, addresses: None};
+        let _res = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+
+        
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ResolveAttributes { name : String::from("testname")}).unwrap();
+        let value: NameResponse = from_binary(&res).unwrap();
+        assert_eq!(Name {id: String::from("testname") , expires: 1571797419879305533 + Timestamp::from_seconds(SECONDS_IN_YEAR * 2).nanos() , owner: Addr::unchecked("annie"), approvals: vec![], records: vec![], addresses: vec![]}, value.name);
+
+    }
+
+    #[test]
+    fn text_records() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, int_mgs()).unwrap();
+
+        let records = vec![
+            TextRecord { key: String::from("url"), value: String::from("https://jackal.example") },
+            TextRecord { key: String::from("com.twitter"), value: String::from("@first") },
+            TextRecord { key: String::from("com.twitter"), value: String::from("@annie") },
+        ];
+        let auth_info = mock_info("annie", &coins(200000, "ujuno"));
+        let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years: 1 , records: Some(records), addresses: None};
+        let _res = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+
+        // sorted by key, the later duplicate wins
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetRecords { name: String::from("testname"), keys: None }).unwrap();
+        let value: RecordsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![
+            TextRecord { key: String::from("com.twitter"), value: String::from("@annie") },
+            TextRecord { key: String::from("url"), value: String::from("https://jackal.example") },
+        ], value.records);
+
+        let annie = mock_info("annie", &[]);
+        let msg = ExecuteMsg::SetRecord { name: String::from("testname"), key: String::from("avatar"), value: String::from("example.com/a.png") };
+        let _res = execute(deps.as_mut(), mock_env(), annie.clone(), msg).unwrap();
+        let msg = ExecuteMsg::RemoveRecord { name: String::from("testname"), key: String::from("url") };
+        let _res = execute(deps.as_mut(), mock_env(), annie.clone(), msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetRecords { name: String::from("testname"), keys: Some(vec![String::from("avatar"), String::from("url")]) }).unwrap();
+        let value: RecordsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![TextRecord { key: String::from("avatar"), value: String::from("example.com/a.png") }], value.records);
+
+        // removing a missing record fails
+        let msg = ExecuteMsg::RemoveRecord { name: String::from("testname"), key: String::from("url") };
+        assert!(matches!(execute(deps.as_mut(), mock_env(), annie.clone(), msg), Err(ContractError::RecordNotFound { .. })));
+
+        // keys are validated
+        for key in ["", "Com.Twitter", "two words"] {
+            let msg = ExecuteMsg::SetRecord { name: String::from("testname"), key: String::from(key), value: String::from("x") };
+            assert!(matches!(execute(deps.as_mut(), mock_env(), annie.clone(), msg), Err(ContractError::InvalidRecord { .. })));
+        }
+
+        // only the owner can change records
+        let bobby = mock_info("bobby", &[]);
+        let msg = ExecuteMsg::SetRecord { name: String::from("testname"), key: String::from("email"), value: String::from("bob@example.com") };
+        match execute(deps.as_mut(), mock_env(), bobby.clone(), msg) {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let msg = ExecuteMsg::RemoveRecord { name: String::from("testname"), key: String::from("avatar") };
+        match execute(deps.as_mut(), mock_env(), bobby, msg) {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+    }
+
+    #[test]
+    fn chain_addresses() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, int_mgs()).unwrap();
+
+        let osmo = String::from("osmo1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5helwsw");
+        let cosmos = String::from("cosmos1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5lzv7xu");
+        let addresses = vec![
+            ChainAddress { chain: String::from("osmo"), address: osmo.clone() },
+            ChainAddress { chain: String::from("cosmos"), address: cosmos.to_uppercase() },
+        ];
+        let auth_info = mock_info("annie", &coins(200000, "ujuno"));
+        let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years: 1 , records: None, addresses: Some(addresses)};
+        let _res = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ResolveAddress { name: String::from("testname"), chain: String::from("osmo") }).unwrap();
+        let value: AddressResponse = from_binary(&res).unwrap();
+        assert_eq!(osmo, value.address);
+
+        // addresses are stored lowercase
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ResolveAddress { name: String::from("testname"), chain: String::from("cosmos") }).unwrap();
+        let value: AddressResponse = from_binary(&res).unwrap();
+        assert_eq!(cosmos, value.address);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ResolveAddress { name: String::from("testname"), chain: String::from("terra") });
+        match res {
+            Err(StdError::NotFound { .. }) => {}
+            _ => panic!("Must return not found"),
+        }
+
+        // the address has to carry the chain's prefix and a valid checksum
+        let invalid = [
+            ("juno", osmo.clone()),
+            ("osmo", String::from("osmo1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5helwsq")),
+            ("osmo", String::from("testname")),
+        ];
+        for (chain, address) in invalid {
+            let addresses = Some(vec![AddressUpdate { chain: String::from(chain), address: Some(address) }]);
+            let msg = ExecuteMsg::UpdateParams { name: String::from("testname"), records: None, addresses };
+            match execute(deps.as_mut(), mock_env(), mock_info("annie", &[]), msg) {
+                Err(ContractError::InvalidAddress { .. }) => {}
+                res => panic!("Must return invalid address, got {:?}", res),
+            }
+        }
+    }
+
+    #[test]
+    fn partial_update() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, int_mgs()).unwrap();
+
+        let records = vec![
+            TextRecord { key: String::from("com.twitter"), value: String::from("@annie") },
+            TextRecord { key: String::from("url"), value: String::from("https://jackal.example") },
+        ];
+        let addresses = vec![ChainAddress { chain: String::from("osmo"), address: String::from("osmo1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5helwsw") }];
+        let auth_info = mock_info("annie", &coins(200000, "ujuno"));
+        let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years: 1 , records: Some(records), addresses: Some(addresses.clone())};
+        let _res = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+
+        let annie = mock_info("annie", &[]);
+        let msg = ExecuteMsg::Approve { spender: String::from("bobby"), token_id: String::from("testname"), expires: None };
+        let _res = execute(deps.as_mut(), mock_env(), annie.clone(), msg).unwrap();
+
+        // set the avatar, clear twitter and leave everything else alone
+        let msg = ExecuteMsg::UpdateParams {
+            name: String::from("testname"),
+            records: Some(vec![
+                RecordUpdate { key: String::from("avatar"), value: Some(String::from("example.com/a.png")) },
+                RecordUpdate { key: String::from("com.twitter"), value: None },
+            ]),
+            addresses: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), annie.clone(), msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ResolveAttributes { name: String::from("testname") }).unwrap();
+        let value: NameResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![
+            TextRecord { key: String::from("avatar"), value: String::from("example.com/a.png") },
+            TextRecord { key: String::from("url"), value: String::from("https://jackal.example") },
+        ], value.name.records);
+        assert_eq!(addresses, value.name.addresses);
+        assert_eq!(1, value.name.approvals.len());
+
+        // addresses can be removed one by one as well
+        let msg = ExecuteMsg::UpdateParams {
+            name: String::from("testname"),
+            records: None,
+            addresses: Some(vec![AddressUpdate { chain: String::from("osmo"), address: None }]),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), annie, msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ResolveAttributes { name: String::from("testname") }).unwrap();
+        let value: NameResponse = from_binary(&res).unwrap();
+        assert!(value.name.addresses.is_empty());
+        assert_eq!(2, value.name.records.len());
+
+        // still owner only
+        let msg = ExecuteMsg::UpdateParams { name: String::from("testname"), records: None, addresses: None };
+        match execute(deps.as_mut(), mock_env(), mock_info("bobby", &[]), msg) {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+    }
+
+    #[test]
+    fn subdomains() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, int_mgs()).unwrap();
+
+        let auth_info = mock_info("annie", &coins(200000, "ujuno"));
+        let msg = ExecuteMsg::RegisterName { name: String::from("alice") , years: 1 , records: None, addresses: None};
+        let _res = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+        let parent_expires = mock_env().block.time.plus_seconds(SECONDS_IN_YEAR).nanos();
+
+        // dotted names can't be bought from the contract
+        let auth_info = mock_info("bobby", &coins(200000, "ujuno"));
+        let msg = ExecuteMsg::RegisterName { name: String::from("team.alice") , years: 1 , records: None, addresses: None};
+        assert!(matches!(execute(deps.as_mut(), mock_env(), auth_info, msg), Err(ContractError::InvalidName { .. })));
+
+        // only the parent owner can create subdomains
+        let msg = ExecuteMsg::CreateSubdomain { parent: String::from("alice"), label: String::from("team"), owner: String::from("bobby"), expires: None };
+        match execute(deps.as_mut(), mock_env(), mock_info("bobby", &[]), msg.clone()) {
+            Err(ContractError::Unauthorized {}) => {}
+            res => panic!("Must return unauthorized error, got {:?}", res),
+        }
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("annie", &[]), msg.clone()).unwrap();
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("annie", &[]), msg), Err(ContractError::NameTaken { .. })));
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ResolveName { name: String::from("Team.Alice") }).unwrap();
+        let value: OwnerResponse = from_binary(&res).unwrap();
+        assert_eq!(Addr::unchecked("bobby"), value.owner);
+
+        // the subdomain owner manages its records and can go deeper, capped by the parent
+        let msg = ExecuteMsg::SetRecord { name: String::from("team.alice"), key: String::from("url"), value: String::from("https://team.example") };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("bobby", &[]), msg).unwrap();
+        let msg = ExecuteMsg::CreateSubdomain { parent: String::from("team.alice"), label: String::from("dev"), owner: String::from("carl"), expires: Some(u64::MAX) };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("bobby", &[]), msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ResolveAttributes { name: String::from("dev.team.alice") }).unwrap();
+        let value: NameResponse = from_binary(&res).unwrap();
+        assert_eq!(Addr::unchecked("carl"), value.name.owner);
+        assert_eq!(parent_expires, value.name.expires);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Subdomains { parent: String::from("alice"), start_after: None, limit: None }).unwrap();
+        let value: TokensResponse = from_binary(&res).unwrap();
+        assert_eq!(vec!["team.alice"], value.tokens);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::NumTokens {}).unwrap();
+        let value: NumTokensResponse = from_binary(&res).unwrap();
+        assert_eq!(3, value.tokens);
+
+        // subdomains stop resolving with their parent
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(SECONDS_IN_YEAR + 1);
+        assert!(query(deps.as_ref(), env, QueryMsg::ResolveName { name: String::from("dev.team.alice") }).is_err());
+
+        // the subdomain owner can't revoke it, the parent owner removes the whole tree
+        let msg = ExecuteMsg::RevokeSubdomain { name: String::from("team.alice") };
+        match execute(deps.as_mut(), mock_env(), mock_info("bobby", &[]), msg.clone()) {
+            Err(ContractError::Unauthorized {}) => {}
+            res => panic!("Must return unauthorized error, got {:?}", res),
+        }
+        let res = execute(deps.as_mut(), mock_env(), mock_info("annie", &[]), msg).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "names_removed" && a.value == "2"));
+
+        assert!(query(deps.as_ref(), mock_env(), QueryMsg::ResolveName { name: String::from("team.alice") }).is_err());
+        assert!(query(deps.as_ref(), mock_env(), QueryMsg::ResolveName { name: String::from("dev.team.alice") }).is_err());
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::NumTokens {}).unwrap();
+        let value: NumTokensResponse = from_binary(&res).unwrap();
+        assert_eq!(1, value.tokens);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Tokens { owner: String::from("carl"), start_after: None, limit: None, include_expired: Some(true) }).unwrap();
+        let value: TokensResponse = from_binary(&res).unwrap();
+        assert!(value.tokens.is_empty());
+
+        // top level names are not revoked this way
+        let msg = ExecuteMsg::RevokeSubdomain { name: String::from("alice") };
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("annie", &[]), msg), Err(ContractError::NotSubdomain { .. })));
+    }
+
+    #[test]
+    fn subdomain_extension() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, int_mgs()).unwrap();
+
+        let msg = ExecuteMsg::RegisterName { name: String::from("alice") , years: 1 , records: None, addresses: None};
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("annie", &coins(200000, "ujuno")), msg).unwrap();
+        let day = mock_env().block.time.plus_seconds(SECONDS_IN_DAY).nanos();
+        let msg = ExecuteMsg::CreateSubdomain { parent: String::from("alice"), label: String::from("team"), owner: String::from("bobby"), expires: Some(day) };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("annie", &[]), msg).unwrap();
+
+        // the parent owner renews and carries the subdomain along
+        let msg = ExecuteMsg::AddTime { name: String::from("alice"), years: 1 };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("annie", &coins(200000, "ujuno")), msg).unwrap();
+
+        let extend = ExecuteMsg::ExtendSubdomain { name: String::from("team.alice"), expires: None };
+        match execute(deps.as_mut(), mock_env(), mock_info("bobby", &[]), extend.clone()) {
+            Err(ContractError::Unauthorized {}) => {}
+            res => panic!("Must return unauthorized error, got {:?}", res),
+        }
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("annie", &[]), extend).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ResolveAttributes { name: String::from("team.alice") }).unwrap();
+        let value: NameResponse = from_binary(&res).unwrap();
+        assert_eq!(mock_env().block.time.plus_seconds(2 * SECONDS_IN_YEAR).nanos(), value.name.expires);
+
+        // capped by the parent and never shortened
+        for expires in [u64::MAX, day] {
+            let extend = ExecuteMsg::ExtendSubdomain { name: String::from("team.alice"), expires: Some(expires) };
+            let _res = execute(deps.as_mut(), mock_env(), mock_info("annie", &[]), extend).unwrap();
+            let res = query(deps.as_ref(), mock_env(), QueryMsg::ResolveAttributes { name: String::from("team.alice") }).unwrap();
+            let value: NameResponse = from_binary(&res).unwrap();
+            assert_eq!(mock_env().block.time.plus_seconds(2 * SECONDS_IN_YEAR).nanos(), value.name.expires);
+        }
+
+        let extend = ExecuteMsg::ExtendSubdomain { name: String::from("other.alice"), expires: None };
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("annie", &[]), extend), Err(ContractError::NameNotFound { .. })));
+        let extend = ExecuteMsg::ExtendSubdomain { name: String::from("alice"), expires: None };
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("annie", &[]), extend), Err(ContractError::NotSubdomain { .. })));
+    }
+
+    #[test]
+    fn subdomain_sales() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let mut msg = int_mgs();
+        msg.subdomain_fee_bps = Some(500);
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let auth_info = mock_info("annie", &coins(200000, "ujuno"));
+        let msg = ExecuteMsg::RegisterName { name: String::from("alice") , years: 1 , records: None, addresses: None};
+        let _res = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+
+        // nothing is for sale until the parent owner sets a price
+        let buy = ExecuteMsg::BuySubdomain { parent: String::from("alice"), label: String::from("shop") };
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("bobby", &coins(1000, "ujuno")), buy.clone()), Err(ContractError::NotForSale { .. })));
+        assert!(query(deps.as_ref(), mock_env(), QueryMsg::SubdomainPriceQuote { parent: String::from("alice") }).is_err());
+
+        let msg = ExecuteMsg::SetSubdomainPrice { parent: String::from("alice"), price: Some(Uint128::new(1000)) };
+        match execute(deps.as_mut(), mock_env(), mock_info("bobby", &[]), msg.clone()) {
+            Err(ContractError::Unauthorized {}) => {}
+            res => panic!("Must return unauthorized error, got {:?}", res),
+        }
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("annie", &[]), msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::SubdomainPriceQuote { parent: String::from("alice") }).unwrap();
+        let value: SubdomainPriceResponse = from_binary(&res).unwrap();
+        assert_eq!(SubdomainPriceResponse { parent: String::from("alice"), denom: String::from("ujuno"), price: Uint128::new(1000), protocol_fee: Uint128::new(50), parent_share: Uint128::new(950) }, value);
+
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("bobby", &coins(999, "ujuno")), buy.clone()), Err(ContractError::InsufficientFunds { .. })));
+
+        // the parent owner is paid their share right away
+        let res = execute(deps.as_mut(), mock_env(), mock_info("bobby", &coins(1000, "ujuno")), buy.clone()).unwrap();
+        assert_eq!(1, res.messages.len());
+        assert_eq!(
+            res.messages[0].msg,
+            BankMsg::Send { to_address: String::from("annie"), amount: coins(950, "ujuno") }.into()
+        );
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ResolveAttributes { name: String::from("shop.alice") }).unwrap();
+        let value: NameResponse = from_binary(&res).unwrap();
+        assert_eq!(Addr::unchecked("bobby"), value.name.owner);
+        assert_eq!(mock_env().block.time.plus_seconds(SECONDS_IN_YEAR).nanos(), value.name.expires);
+
+        // taken now
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("carl", &coins(1000, "ujuno")), buy), Err(ContractError::NameTaken { .. })));
+
+        // nor through a subdomain someone else sold further down
+        let msg = ExecuteMsg::CreateSubdomain { parent: String::from("alice"), label: String::from("team"), owner: String::from("bobby"), expires: None };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("annie", &[]), msg).unwrap();
+        let msg = ExecuteMsg::SetSubdomainPrice { parent: String::from("team.alice"), price: Some(Uint128::new(10)) };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("bobby", &[]), msg).unwrap();
+        let msg = ExecuteMsg::BuySubdomain { parent: String::from("team.alice"), label: String::from("shop") };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("carl", &coins(10, "ujuno")), msg).unwrap();
+        let msg = ExecuteMsg::RevokeSubdomain { name: String::from("team.alice") };
+        match execute(deps.as_mut(), mock_env(), mock_info("annie", &[]), msg) {
+            Err(ContractError::SubdomainSold { name }) => assert_eq!("shop.team.alice", name),
+            res => panic!("Must refuse to revoke a sold subdomain, got {:?}", res),
+        }
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ResolveName { name: String::from("shop.team.alice") }).unwrap();
+        let value: OwnerResponse = from_binary(&res).unwrap();
+        assert_eq!(Addr::unchecked("carl"), value.owner);
+
+        // the parent owner can't take back what they were paid for
+        let revoke = ExecuteMsg::RevokeSubdomain { name: String::from("shop.alice") };
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("annie", &[]), revoke.clone()), Err(ContractError::SubdomainSold { .. })));
+
+        // until the term the buyer paid for is over
+        let msg = ExecuteMsg::AddTime { name: String::from("alice"), years: 1 };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("annie", &coins(200000, "ujuno")), msg).unwrap();
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(SECONDS_IN_YEAR);
+        let _res = execute(deps.as_mut(), env, mock_info("annie", &[]), revoke).unwrap();
+
+        // a later denom change doesn't reprice the sale
+        let msg = ExecuteMsg::UpdateConfig(ConfigUpdate { denom: Some(String::from("uatom")), ..ConfigUpdate::default() });
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let buy = ExecuteMsg::BuySubdomain { parent: String::from("alice"), label: String::from("other") };
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("carl", &coins(1000, "uatom")), buy.clone()), Err(ContractError::UnsupportedDenom { .. })));
+        let res = execute(deps.as_mut(), mock_env(), mock_info("carl", &coins(1000, "ujuno")), buy).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            BankMsg::Send { to_address: String::from("annie"), amount: coins(950, "ujuno") }.into()
+        );
+
+        // the parent owner can stop sales again
+        let msg = ExecuteMsg::SetSubdomainPrice { parent: String::from("alice"), price: None };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("annie", &[]), msg).unwrap();
+        let buy = ExecuteMsg::BuySubdomain { parent: String::from("alice"), label: String::from("other") };
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("carl", &coins(1000, "ujuno")), buy), Err(ContractError::NotForSale { .. })));
+    }
+
+    #[test]
+    fn payment_refunds() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, int_mgs()).unwrap();
+
+        // anything sent over the price comes straight back
+        let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years: 2 , records: None, addresses: None};
+        let res = execute(deps.as_mut(), mock_env(), mock_info("annie", &coins(10, "ujuno")), msg).unwrap();
+        assert_eq!(1, res.messages.len());
+        assert_eq!(
+            res.messages[0].msg,
+            BankMsg::Send { to_address: String::from("annie"), amount: coins(8, "ujuno") }.into()
+        );
+
+        // an exact payment has nothing to refund
+        let msg = ExecuteMsg::AddTime { name: String::from("testname"), years: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("annie", &coins(1, "ujuno")), msg.clone()).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // renewals refund whoever paid
+        let res = execute(deps.as_mut(), mock_env(), mock_info("bobby", &coins(5, "ujuno")), msg.clone()).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            BankMsg::Send { to_address: String::from("bobby"), amount: coins(4, "ujuno") }.into()
+        );
+
+        // other denoms are rejected, and payments are made in a single denom
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("annie", &coins(100, "uatom")), msg.clone()), Err(ContractError::UnsupportedDenom { .. })));
+        let funds = vec![Coin { denom: String::from("ujuno"), amount: Uint128::new(1) }, Coin { denom: String::from("uatom"), amount: Uint128::new(1) }];
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("annie", &funds), msg), Err(ContractError::Payment(PaymentError::MultipleDenoms {}))));
+        let msg = ExecuteMsg::RegisterName { name: String::from("other") , years: 1 , records: None, addresses: None};
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("annie", &funds), msg), Err(ContractError::Payment(PaymentError::MultipleDenoms {}))));
+
+        // subdomain sales refund the buyer on top of paying the parent owner
+        let msg = ExecuteMsg::SetSubdomainPrice { parent: String::from("testname"), price: Some(Uint128::new(1000)) };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("annie", &[]), msg).unwrap();
+        let buy = ExecuteMsg::BuySubdomain { parent: String::from("testname"), label: String::from("shop") };
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("bobby", &coins(1000, "uatom")), buy.clone()), Err(ContractError::UnsupportedDenom { .. })));
+        let res = execute(deps.as_mut(), mock_env(), mock_info("bobby", &coins(1500, "ujuno")), buy).unwrap();
+        assert_eq!(2, res.messages.len());
+        assert_eq!(
+            res.messages[0].msg,
+            BankMsg::Send { to_address: String::from("bobby"), amount: coins(500, "ujuno") }.into()
+        );
+        assert_eq!(
+            res.messages[1].msg,
+            BankMsg::Send { to_address: String::from("annie"), amount: coins(1000, "ujuno") }.into()
+        );
+    }
+
+    #[test]
+    fn extra_denoms() {
+        let usdc = "ibc/D189335C6E4A68B513C10AB227BF1C1D38C746766278BA3EEB4FB14124F1D858";
+        let mut deps = mock_dependencies_with_balance(&[coin(10, "ujuno"), coin(20, "uatom")]);
+
+        let mut msg = int_mgs();
+        msg.extra_denoms = Some(vec![
+            DenomPrices { denom: String::from("uatom"), prices: vec![PriceTier { min_length: 1, cost: Uint128::new(3200) }, PriceTier { min_length: 6, cost: Uint128::new(100) }] },
+            DenomPrices { denom: String::from(usdc), prices: vec![PriceTier { min_length: 1, cost: Uint128::new(64) }, PriceTier { min_length: 6, cost: Uint128::new(2) }] },
+        ]);
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // quotes cover every accepted denom
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::PriceQuote { name: String::from("testname"), years: 2 }).unwrap();
+        let value: PriceResponse = from_binary(&res).unwrap();
+        assert_eq!(("ujuno", 2), (value.denom.as_str(), value.total.u128()));
+        assert_eq!(vec![
+            DenomQuote { denom: String::from("uatom"), cost_per_year: Uint128::new(100), total: Uint128::new(200) },
+            DenomQuote { denom: String::from(usdc), cost_per_year: Uint128::new(2), total: Uint128::new(4) },
+        ], value.other_denoms);
+        assert_eq!(None, value.main_denom_only);
+
+        // the payer picks the denom, refunds come back in it
+        let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years: 2 , records: None, addresses: None};
+        let res = execute(deps.as_mut(), mock_env(), mock_info("annie", &coins(250, "uatom")), msg).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "denom" && a.value == "uatom"));
+        assert_eq!(
+            res.messages[0].msg,
+            BankMsg::Send { to_address: String::from("annie"), amount: coins(50, "uatom") }.into()
+        );
+
+        let msg = ExecuteMsg::AddTime { name: String::from("testname"), years: 1 };
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("annie", &coins(1, usdc)), msg.clone()), Err(ContractError::InsufficientFunds { .. })));
+        let res = execute(deps.as_mut(), mock_env(), mock_info("annie", &coins(2, usdc)), msg.clone()).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("annie", &coins(1000, "uosmo")), msg), Err(ContractError::UnsupportedDenom { .. })));
+
+        // names with their own price are only sold in the main denom
+        let msg = ExecuteMsg::SetPriceOverrides { prices: vec![NamePrice { name: String::from("defi"), cost: Uint128::new(500) }] };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::RegistrationQuote { name: String::from("defi"), years: 1 }).unwrap();
+        let value: RegistrationQuoteResponse = from_binary(&res).unwrap();
+        assert!(value.other_denoms.is_empty());
+        assert_eq!(Some(MainDenomOnly::PriceOverride), value.main_denom_only);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::PriceQuote { name: String::from("defi"), years: 1 }).unwrap();
+        let value: PriceResponse = from_binary(&res).unwrap();
+        assert!(value.other_denoms.is_empty());
+        assert_eq!(Some(MainDenomOnly::PriceOverride), value.main_denom_only);
+        let msg = ExecuteMsg::RegisterName { name: String::from("defi") , years: 1 , records: None, addresses: None};
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("annie", &coins(5000, "uatom")), msg), Err(ContractError::UnsupportedDenom { .. })));
+
+        // withdrawing reports every denom
+        let msg = ExecuteMsg::WithdrawBalance {};
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("annie", &[]), msg.clone()), Err(ContractError::Unauthorized {})));
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let withdrawn: Vec<&str> = res.attributes.iter().filter(|a| a.key == "withdrawn").map(|a| a.value.as_str()).collect();
+        assert_eq!(vec!["10ujuno", "20uatom"], withdrawn);
+
+        // each denom is listed once
+        for denoms in [vec!["ujuno"], vec!["uatom", "uatom"], vec![""]] {
+            let mut msg = int_mgs();
+            msg.extra_denoms = Some(denoms.iter().map(|d| DenomPrices { denom: d.to_string(), prices: vec![PriceTier { min_length: 1, cost: Uint128::new(1) }] }).collect());
+            let mut deps = mock_dependencies_with_balance(&[]);
+            assert!(matches!(instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg), Err(ContractError::InvalidConfig { .. })));
+        }
+    }
+
+    #[test]
+    fn subdomain_fee_split() {
+        let split = |price: u128, fee_bps: u64| {
+            let (fee, share) = split_subdomain_price(Uint128::new(price), fee_bps);
+            (fee.u128(), share.u128())
+        };
+        assert_eq!((0, 1000), split(1000, 0));
+        assert_eq!((50, 950), split(1000, 500));
+        assert_eq!((1000, 0), split(1000, 10_000));
+        assert_eq!((0, 3), split(3, 1));
+        assert_eq!((u128::MAX / 2, u128::MAX - u128::MAX / 2), split(u128::MAX, 5_000));
+
+        let mut msg = int_mgs();
+        msg.subdomain_fee_bps = Some(10_001);
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+        assert!(matches!(instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg), Err(ContractError::InvalidConfig { .. })));
+    }
+
+    #[test]
+    fn auctions() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let mut msg = int_mgs();
+        msg.auction_duration = Some(100);
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // fresh names are only put up by the admin
+        let start = ExecuteMsg::StartAuction { name: String::from("x"), min_bid: Some(Uint128::new(1000)) };
+        match execute(deps.as_mut(), mock_env(), mock_info("bobby", &[]), start.clone()) {
+            Err(ContractError::Unauthorized {}) => {}
+            res => panic!("Must return unauthorized error, got {:?}", res),
+        }
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), start.clone()).unwrap();
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), start), Err(ContractError::NameInAuction { .. })));
+
+        // no sniping it at the fixed price meanwhile
+        let msg = ExecuteMsg::RegisterName { name: String::from("x") , years: 1 , records: None, addresses: None};
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("annie", &coins(200000, "ujuno")), msg), Err(ContractError::NameInAuction { .. })));
+
+        let bid = ExecuteMsg::Bid { name: String::from("x") };
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("bobby", &coins(999, "ujuno")), bid.clone()), Err(ContractError::BidTooLow { .. })));
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("bobby", &coins(1000, "uatom")), bid.clone()), Err(ContractError::Payment(_))));
+        let res = execute(deps.as_mut(), mock_env(), mock_info("bobby", &coins(1000, "ujuno")), bid.clone()).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // bids stay in the auction's denom even if the config moves on
+        let mut state = STATE.load(deps.as_ref().storage).unwrap();
+        state.denom = String::from("uatom");
+        STATE.save(deps.as_mut().storage, &state).unwrap();
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("carl", &coins(5000, "uatom")), bid.clone()), Err(ContractError::Payment(_))));
+
+        // the next bid has to be 5% higher and refunds the one it beats
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("carl", &coins(1049, "ujuno")), bid.clone()), Err(ContractError::BidTooLow { .. })));
+        let res = execute(deps.as_mut(), mock_env(), mock_info("carl", &coins(1050, "ujuno")), bid.clone()).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            BankMsg::Send { to_address: String::from("bobby"), amount: coins(1000, "ujuno") }.into()
+        );
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Auction { name: String::from("x") }).unwrap();
+        let value: Auction = from_binary(&res).unwrap();
+        assert_eq!(Uint128::new(1050), value.highest_bid);
+        assert_eq!(Some(Addr::unchecked("carl")), value.highest_bidder);
+
+        // the escrowed bid isn't the owner's to withdraw
+        deps.querier.update_balance(MOCK_CONTRACT_ADDR, vec![coin(1350, "ujuno"), coin(20, "uatom")]);
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), ExecuteMsg::WithdrawBalance {}).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            BankMsg::Send { to_address: String::from("creator"), amount: vec![coin(300, "ujuno"), coin(20, "uatom")] }.into()
+        );
+        deps.querier.update_balance(MOCK_CONTRACT_ADDR, coins(1050, "ujuno"));
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), ExecuteMsg::WithdrawBalance {}).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        let close = ExecuteMsg::CloseAuction { name: String::from("x") };
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), close.clone()), Err(ContractError::AuctionRunning { .. })));
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(100);
+        assert!(matches!(execute(deps.as_mut(), env.clone(), mock_info("bobby", &coins(5000, "ujuno")), bid), Err(ContractError::AuctionEnded { .. })));
+        let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), close).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "winner" && a.value == "carl"));
+
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::ResolveName { name: String::from("x") }).unwrap();
+        let value: OwnerResponse = from_binary(&res).unwrap();
+        assert_eq!(Addr::unchecked("carl"), value.owner);
+
+        let res = query(deps.as_ref(), env, QueryMsg::Auctions { start_after: None, limit: None }).unwrap();
+        let value: AuctionsResponse = from_binary(&res).unwrap();
+        assert!(value.auctions.is_empty());
+    }
+
+    #[test]
+    fn auction_after_expiry() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, int_mgs()).unwrap();
+
+        let msg = ExecuteMsg::RegisterName { name: String::from("abc") , years: 1 , records: None, addresses: None};
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("annie", &coins(200000, "ujuno")), msg).unwrap();
+
+        // not while the owner still holds it
+        let start = ExecuteMsg::StartAuction { name: String::from("abc"), min_bid: None };
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("bobby", &[]), start.clone()), Err(ContractError::NameTaken { .. })));
+
+        // once it expired anyone can open one, starting at a year's price
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(SECONDS_IN_YEAR + 1);
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("bobby", &[]), start).unwrap();
+
+        // only the admin picks the starting bid
+        let start = ExecuteMsg::StartAuction { name: String::from("xyz"), min_bid: Some(Uint128::new(1)) };
+        assert!(matches!(execute(deps.as_mut(), env.clone(), mock_info("bobby", &[]), start), Err(ContractError::Unauthorized { .. })));
+
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::Auctions { start_after: None, limit: None }).unwrap();
+        let value: AuctionsResponse = from_binary(&res).unwrap();
+        assert_eq!(1, value.auctions.len());
+        assert_eq!(Uint128::new(8), value.auctions[0].min_bid);
+
+        // closing without bids leaves the name available
+        env.block.time = env.block.time.plus_seconds(DEFAULT_AUCTION_DURATION);
+        let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), ExecuteMsg::CloseAuction { name: String::from("abc") }).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "winner" && a.value == "none"));
+
+        let msg = ExecuteMsg::RegisterName { name: String::from("abc") , years: 1 , records: None, addresses: None};
+        let _res = execute(deps.as_mut(), env, mock_info("bobby", &coins(200000, "ujuno")), msg).unwrap();
+    }
+
+    #[test]
+    fn expiry_premium_decay() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, int_mgs()).unwrap();
+
+        let mut state = STATE.load(deps.as_ref().storage).unwrap();
+        state.premium = Uint128::new(1_000_000);
+        state.premium_days = 4;
+        let day = Timestamp::from_seconds(SECONDS_IN_DAY).nanos();
+        let expires = 1_000;
+
+        assert_eq!(Uint128::new(1_000_000), expiry_premium(&state, expires, expires));
+        assert_eq!(Uint128::new(750_000), expiry_premium(&state, expires, expires + day));
+        assert_eq!(Uint128::new(500_000), expiry_premium(&state, expires, expires + 2 * day));
+        assert_eq!(Uint128::new(0), expiry_premium(&state, expires, expires + 4 * day));
+        assert_eq!(Uint128::new(0), expiry_premium(&state, expires, expires - 1));
+
+        // halving daily, less the 1/16th that would be left after four days
+        state.premium_decay = PremiumDecay::Exponential;
+        assert_eq!(Uint128::new(937_500), expiry_premium(&state, expires, expires));
+        assert_eq!(Uint128::new(437_500), expiry_premium(&state, expires, expires + day));
+        assert_eq!(Uint128::new(187_500), expiry_premium(&state, expires, expires + 2 * day));
+        assert_eq!(Uint128::new(687_500), expiry_premium(&state, expires, expires + day / 2));
+        assert_eq!(Uint128::new(0), expiry_premium(&state, expires, expires + 4 * day));
+        assert!(expiry_premium(&state, expires, expires + 4 * day - 1) < Uint128::new(10));
+
+        state.premium_days = 1000;
+        assert_eq!(Uint128::new(0), expiry_premium(&state, expires, expires + 200 * day));
+    }
+
+    #[test]
+    fn register_with_premium() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let mut msg = int_mgs();
+        msg.premium = Some(Uint128::new(1000));
+        msg.premium_days = Some(10);
+        msg.grace_period = Some(2 * SECONDS_IN_DAY);
+        msg.redemption_period = Some(3 * SECONDS_IN_DAY);
+        msg.extra_denoms = Some(vec![DenomPrices { denom: String::from("uatom"), prices: vec![PriceTier { min_length: 1, cost: Uint128::new(3) }] }]);
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // brand new names carry no premium
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::RegistrationQuote { name: String::from("testname"), years: 1 }).unwrap();
+        let value: RegistrationQuoteResponse = from_binary(&res).unwrap();
+        assert_eq!((1, 0, 1), (value.base.u128(), value.premium.u128(), value.total.u128()));
+        assert_eq!((1, None), (value.other_denoms.len(), value.main_denom_only));
+
+        let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years: 1 , records: None, addresses: None};
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("annie", &coins(1, "ujuno")), msg.clone()).unwrap();
+
+        // the decay only starts once grace and redemption are over
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(SECONDS_IN_YEAR + 5 * SECONDS_IN_DAY);
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::RegistrationQuote { name: String::from("testname"), years: 1 }).unwrap();
+        let value: RegistrationQuoteResponse = from_binary(&res).unwrap();
+        assert_eq!(Uint128::new(1000), value.premium);
+        // a premium is only priced in the main denom
+        assert!(value.other_denoms.is_empty());
+        assert_eq!(Some(MainDenomOnly::ExpiryPremium), value.main_denom_only);
+
+        // half way through the decay the premium is halved
+        env.block.time = env.block.time.plus_seconds(5 * SECONDS_IN_DAY);
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::RegistrationQuote { name: String::from("testname"), years: 2 }).unwrap();
+        let value: RegistrationQuoteResponse = from_binary(&res).unwrap();
+        assert_eq!((2, 500, 502), (value.base.u128(), value.premium.u128(), value.total.u128()));
+
+        let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years: 2 , records: None, addresses: None};
+        assert!(matches!(execute(deps.as_mut(), env.clone(), mock_info("bobby", &coins(2, "ujuno")), msg.clone()), Err(ContractError::InsufficientFunds { .. })));
+        let _res = execute(deps.as_mut(), env, mock_info("bobby", &coins(502, "ujuno")), msg).unwrap();
+    }
+
+    #[test]
+    fn auction_keeps_premium() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let mut msg = int_mgs();
+        msg.premium = Some(Uint128::new(1000));
+        msg.premium_days = Some(10);
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years: 1 , records: None, addresses: None};
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("annie", &coins(1, "ujuno")), msg).unwrap();
+
+        // opening an auction starts at what registering would cost
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(SECONDS_IN_YEAR + 5 * SECONDS_IN_DAY);
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::RegistrationQuote { name: String::from("testname"), years: 1 }).unwrap();
+        let quote: RegistrationQuoteResponse = from_binary(&res).unwrap();
+        assert_eq!(Uint128::new(501), quote.total);
+
+        let start = ExecuteMsg::StartAuction { name: String::from("testname"), min_bid: None };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("bobby", &[]), start).unwrap();
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::Auction { name: String::from("testname") }).unwrap();
+        let auction: Auction = from_binary(&res).unwrap();
+        assert_eq!(quote.total, auction.min_bid);
+
+        let bid = ExecuteMsg::Bid { name: String::from("testname") };
+        assert!(matches!(execute(deps.as_mut(), env, mock_info("bobby", &coins(1, "ujuno")), bid), Err(ContractError::BidTooLow { .. })));
+    }
+
+    #[test]
+    fn reserved_names() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, int_mgs()).unwrap();
+
+        let msg = ExecuteMsg::AddReserved { names: vec![String::from("Jackal"), String::from("juno"), String::from("atom")] };
+        match execute(deps.as_mut(), mock_env(), mock_info("annie", &[]), msg.clone()) {
+            Err(ContractError::Unauthorized {}) => {}
+            res => panic!("Must return unauthorized error, got {:?}", res),
+        }
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Reserved { start_after: None, limit: Some(2) }).unwrap();
+        let value: ReservedResponse = from_binary(&res).unwrap();
+        assert_eq!(vec!["atom", "jackal"], value.names);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Reserved { start_after: Some(String::from("jackal")), limit: None }).unwrap();
+        let value: ReservedResponse = from_binary(&res).unwrap();
+        assert_eq!(vec!["juno"], value.names);
+
+        let msg = ExecuteMsg::RegisterName { name: String::from("jackal") , years: 1 , records: None, addresses: None};
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("annie", &coins(200000, "ujuno")), msg), Err(ContractError::NameReserved { .. })));
+
+        // the admin hands it out for free
+        let mint = ExecuteMsg::MintReserved { name: String::from("jackal"), owner: String::from("annie"), years: 2 };
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("annie", &[]), mint.clone()), Err(ContractError::Unauthorized { .. })));
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), mint.clone()).unwrap();
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), mint), Err(ContractError::NameTaken { .. })));
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ResolveAttributes { name: String::from("jackal") }).unwrap();
+        let value: NameResponse = from_binary(&res).unwrap();
+        assert_eq!(Addr::unchecked("annie"), value.name.owner);
+        assert_eq!(mock_env().block.time.plus_seconds(SECONDS_IN_YEAR * 2).nanos(), value.name.expires);
+
+        // a zero year mint would hold the name through grace and redemption for nothing
+        let mint = ExecuteMsg::MintReserved { name: String::from("atom"), owner: String::from("annie"), years: 0 };
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), mint), Err(ContractError::NoYears {})));
+
+        // nor while bidders are competing for it
+        let start = ExecuteMsg::StartAuction { name: String::from("atom"), min_bid: None };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), start).unwrap();
+        let mint = ExecuteMsg::MintReserved { name: String::from("atom"), owner: String::from("annie"), years: 1 };
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), mint), Err(ContractError::NameInAuction { .. })));
+
+        // only reserved names are minted this way
+        let mint = ExecuteMsg::MintReserved { name: String::from("other"), owner: String::from("annie"), years: 1 };
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), mint), Err(ContractError::NotReserved { .. })));
+
+        // released names are open to everyone again
+        let msg = ExecuteMsg::RemoveReserved { names: vec![String::from("juno")] };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let msg = ExecuteMsg::RegisterName { name: String::from("juno") , years: 1 , records: None, addresses: None};
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("bobby", &coins(200000, "ujuno")), msg).unwrap();
+    }
+
+    #[test]
+    fn price_overrides() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, int_mgs()).unwrap();
+
+        let msg = ExecuteMsg::SetPriceOverrides { prices: vec![
+            NamePrice { name: String::from("DeFi"), cost: Uint128::new(500) },
+            NamePrice { name: String::from("nft"), cost: Uint128::new(300) },
+            NamePrice { name: String::from("jackal"), cost: Uint128::new(100) },
+        ] };
+        match execute(deps.as_mut(), mock_env(), mock_info("annie", &[]), msg.clone()) {
+            Err(ContractError::Unauthorized {}) => {}
+            res => panic!("Must return unauthorized error, got {:?}", res),
+        }
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::PriceOverrides { start_after: None, limit: Some(2) }).unwrap();
+        let value: PriceOverridesResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![NamePrice { name: String::from("defi"), cost: Uint128::new(500) }, NamePrice { name: String::from("jackal"), cost: Uint128::new(100) }], value.prices);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::PriceOverrides { start_after: Some(String::from("jackal")), limit: None }).unwrap();
+        let value: PriceOverridesResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![NamePrice { name: String::from("nft"), cost: Uint128::new(300) }], value.prices);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::PriceQuote { name: String::from("defi"), years: 2 }).unwrap();
+        let value: PriceResponse = from_binary(&res).unwrap();
+        assert_eq!((500, 1000), (value.cost_per_year.u128(), value.total.u128()));
+
+        // registration and renewal both use the override instead of the tier price of 4
+        let msg = ExecuteMsg::RegisterName { name: String::from("defi") , years: 1 , records: None, addresses: None};
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("annie", &coins(4, "ujuno")), msg.clone()), Err(ContractError::InsufficientFunds { .. })));
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("annie", &coins(500, "ujuno")), msg).unwrap();
+
+        let msg = ExecuteMsg::AddTime { name: String::from("defi"), years: 1 };
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("annie", &coins(4, "ujuno")), msg.clone()), Err(ContractError::InsufficientFunds { .. })));
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("annie", &coins(500, "ujuno")), msg).unwrap();
+
+        // once removed the tier price applies again
+        let msg = ExecuteMsg::RemovePriceOverrides { names: vec![String::from("defi")] };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::PriceQuote { name: String::from("defi"), years: 1 }).unwrap();
+        let value: PriceResponse = from_binary(&res).unwrap();
+        assert_eq!(Uint128::new(4), value.cost_per_year);
+    }
+
+    #[test]
+    fn reverse_resolve() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let msg = int_mgs();
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let auth_info = mock_info("annie", &coins(200000, "ujuno"));
+        let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years: 1 , records: None, addresses: None};
+        let _res = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+
+        // nothing set yet
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ReverseResolve { address: String::from("annie") }).unwrap();
+        let value: ReverseResolveResponse = from_binary(&res).unwrap();
+        assert_eq!(None, value.name);
+
+        // only the owner can pick the name
+        let bobby = mock_info("bobby", &[]);
+        let res = execute(deps.as_mut(), mock_env(), bobby, ExecuteMsg::SetPrimaryName { name: String::from("testname") });
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
 
-    Ok(ApprovedForAllResponse {operators: ops})
+        let annie = mock_info("annie", &[]);
+        let _res = execute(deps.as_mut(), mock_env(), annie, ExecuteMsg::SetPrimaryName { name: String::from("TestName") }).unwrap();
 
-}
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ReverseResolve { address: String::from("annie") }).unwrap();
+        let value: ReverseResolveResponse = from_binary(&res).unwrap();
+        assert_eq!(Some(String::from("testname")), value.name);
 
-fn query_blocks_per_year(deps: Deps) -> StdResult<BlocksResponse> {
-    let state = STATE.load(deps.storage)?;
-    Ok(BlocksResponse { blocks_per_year: state.blocks_per_year })
-}
+        // an expired name no longer resolves back
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(SECONDS_IN_YEAR + 1);
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::ReverseResolve { address: String::from("annie") }).unwrap();
+        let value: ReverseResolveResponse = from_binary(&res).unwrap();
+        assert_eq!(None, value.name);
 
-fn query_owner(deps: Deps) -> StdResult<OwnerResponse> {
-    let state = STATE.load(deps.storage)?;
-    Ok(OwnerResponse { owner: state.owner })
-}
+        // and re-registering it drops the stale record
+        let auth_info = mock_info("bobby", &coins(200000, "ujuno"));
+        let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years: 1 , records: None, addresses: None};
+        let _res = execute(deps.as_mut(), env, auth_info, msg).unwrap();
+        assert_eq!(None, PRIMARY_NAMES.may_load(deps.as_ref().storage, &Addr::unchecked("annie")).unwrap());
+    }
 
-fn query_name_attributes(deps: Deps, env: Env, name: String) -> StdResult<NameResponse> {
-    let name = name.to_lowercase();
+    #[test]
+    fn transfer_clears_primary_name() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
 
+        let msg = int_mgs();
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    let exists = JNS.may_load(deps.storage, &name);
-    if exists.is_err() {
-        return Err(StdError::NotFound { kind: "Name is not registered.".to_string()});
-    }
+        let auth_info = mock_info("annie", &coins(200000, "ujuno"));
+        let msg = ExecuteMsg::RegisterName { name: String::from("melt") , years: 1 , records: None, addresses: None};
+        let _res = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
 
-    let ret_name = exists.unwrap().unwrap();
+        let annie = mock_info("annie", &[]);
+        let _res = execute(deps.as_mut(), mock_env(), annie.clone(), ExecuteMsg::SetPrimaryName { name: String::from("melt") }).unwrap();
 
-    if ret_name.expires <= env.block.time.nanos() {
-        return Err(StdError::NotFound { kind: "Name is not registered.".to_string()});
-    }
+        let transfer_msg = ExecuteMsg::TransferNft { recipient: "carl".to_string(), token_id: "melt".to_string(), keep_records: None };
+        let _res = execute(deps.as_mut(), mock_env(), annie, transfer_msg).unwrap();
 
-    Ok(NameResponse { name: ret_name })
-}
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ReverseResolve { address: String::from("annie") }).unwrap();
+        let value: ReverseResolveResponse = from_binary(&res).unwrap();
+        assert_eq!(None, value.name);
 
-fn query_name_owner(deps: Deps, env: Env, name: String) -> StdResult<OwnerResponse> {
-    let name = name.to_lowercase();
+        // the new owner has to opt in
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ReverseResolve { address: String::from("carl") }).unwrap();
+        let value: ReverseResolveResponse = from_binary(&res).unwrap();
+        assert_eq!(None, value.name);
 
-    let exists = JNS.may_load(deps.storage, &name);
-    if exists.is_err() {
-        return Err(StdError::NotFound { kind: "Name is not registered.".to_string()});
+        let carl = mock_info("carl", &[]);
+        let _res = execute(deps.as_mut(), mock_env(), carl, ExecuteMsg::SetPrimaryName { name: String::from("melt") }).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ReverseResolve { address: String::from("carl") }).unwrap();
+        let value: ReverseResolveResponse = from_binary(&res).unwrap();
+        assert_eq!(Some(String::from("melt")), value.name);
     }
 
-    let ret_name = exists.unwrap().unwrap();
+    #[test]
+    fn transfer_keep_records() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
 
-    if ret_name.expires <= env.block.time.nanos() {
-        return Err(StdError::NotFound { kind: "Name is not registered.".to_string()});
-    }
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, int_mgs()).unwrap();
 
-    Ok(OwnerResponse { owner: ret_name.owner })
-}
+        let records = vec![
+            TextRecord { key: String::from("avatar"), value: String::from("example.com/a.png") },
+            TextRecord { key: String::from("url"), value: String::from("https://jackal.example") },
+        ];
+        let addresses = vec![ChainAddress { chain: String::from("osmo"), address: String::from("osmo1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5helwsw") }];
+        let auth_info = mock_info("annie", &coins(200000, "ujuno"));
+        let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years: 1 , records: Some(records.clone()), addresses: Some(addresses.clone())};
+        let _res = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use cosmwasm_std::testing::{mock_dependencies_with_balance, mock_env, mock_info};
-    use cosmwasm_std::{coins, from_binary};
+        // moving it between our own wallets keeps everything
+        let transfer_msg = ExecuteMsg::TransferNft { recipient: "cold".to_string(), token_id: "testname".to_string(), keep_records: Some(true) };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("annie", &[]), transfer_msg).unwrap();
+        assert!(!res.attributes.iter().any(|a| a.key == "records_cleared"));
 
-    
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ResolveAttributes { name: String::from("testname") }).unwrap();
+        let value: NameResponse = from_binary(&res).unwrap();
+        assert_eq!(Addr::unchecked("cold"), value.name.owner);
+        assert_eq!(records, value.name.records);
+        assert_eq!(addresses, value.name.addresses);
 
-    fn int_mgs() -> InstantiateMsg{
+        // by default the new owner starts clean and the cleared entries are reported
+        let transfer_msg = ExecuteMsg::TransferNft { recipient: "carl".to_string(), token_id: "testname".to_string(), keep_records: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("cold", &[]), transfer_msg).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "records_cleared" && a.value == "avatar,url"));
+        assert!(res.attributes.iter().any(|a| a.key == "addresses_cleared" && a.value == "osmo"));
 
-        InstantiateMsg { 
-            blocks_per_year: 5048093, 
-            meta_url: "example.com".to_string(),
-            denom: "ujuno".to_string(),
-            cost_for_6: Some(1), 
-            cost_for_5: Some(2), 
-            cost_for_4: Some(4), 
-            cost_for_3: Some(8), 
-            cost_for_2: Some(16), 
-            cost_for_1: Some(32),
-        }
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ResolveAttributes { name: String::from("testname") }).unwrap();
+        let value: NameResponse = from_binary(&res).unwrap();
+        assert!(value.name.records.is_empty());
+        assert!(value.name.addresses.is_empty());
     }
 
     #[test]
-    fn proper_initialization() {
+    fn tokens_by_owner() {
         let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
 
         let msg = int_mgs();
-        let info = mock_info("creator", &coins(1000, "earth"));
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-        // we can just call .unwrap() to assert this was a success
-        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
+        for (name, owner, years) in [("delta", "annie", 2), ("alpha", "annie", 2), ("charlie", "annie", 1), ("bravo", "bobby", 2), ("echo", "annie", 2)] {
+            let auth_info = mock_info(owner, &coins(200000, "ujuno"));
+            let msg = ExecuteMsg::RegisterName { name: String::from(name) , years , records: None, addresses: None};
+            let _res = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+        }
 
-        // it worked, let's query the state
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetBlocksPerYear {}).unwrap();
-        let value: BlocksResponse = from_binary(&res).unwrap();
-        assert_eq!(5048093, value.blocks_per_year);
+        let tokens_msg = |start_after: Option<&str>, limit: Option<u32>, include_expired: Option<bool>| QueryMsg::Tokens {
+            owner: String::from("annie"),
+            start_after: start_after.map(String::from),
+            limit,
+            include_expired,
+        };
+
+        let res = query(deps.as_ref(), mock_env(), tokens_msg(None, None, None)).unwrap();
+        let value: TokensResponse = from_binary(&res).unwrap();
+        assert_eq!(vec!["alpha", "charlie", "delta", "echo"], value.tokens);
+
+        // paginate
+        let res = query(deps.as_ref(), mock_env(), tokens_msg(None, Some(2), None)).unwrap();
+        let value: TokensResponse = from_binary(&res).unwrap();
+        assert_eq!(vec!["alpha", "charlie"], value.tokens);
+        let res = query(deps.as_ref(), mock_env(), tokens_msg(Some("charlie"), Some(2), None)).unwrap();
+        let value: TokensResponse = from_binary(&res).unwrap();
+        assert_eq!(vec!["delta", "echo"], value.tokens);
+
+        // the index follows transfers
+        let annie = mock_info("annie", &[]);
+        let transfer_msg = ExecuteMsg::TransferNft { recipient: "bobby".to_string(), token_id: "delta".to_string(), keep_records: None };
+        let _res = execute(deps.as_mut(), mock_env(), annie, transfer_msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), tokens_msg(None, None, None)).unwrap();
+        let value: TokensResponse = from_binary(&res).unwrap();
+        assert_eq!(vec!["alpha", "charlie", "echo"], value.tokens);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Tokens { owner: String::from("bobby"), start_after: None, limit: None, include_expired: None }).unwrap();
+        let value: TokensResponse = from_binary(&res).unwrap();
+        assert_eq!(vec!["bravo", "delta"], value.tokens);
+
+        // expired names are hidden unless asked for
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(SECONDS_IN_YEAR + 1);
+        let res = query(deps.as_ref(), env.clone(), tokens_msg(None, None, None)).unwrap();
+        let value: TokensResponse = from_binary(&res).unwrap();
+        assert_eq!(vec!["alpha", "echo"], value.tokens);
+        let res = query(deps.as_ref(), env, tokens_msg(None, None, Some(true))).unwrap();
+        let value: TokensResponse = from_binary(&res).unwrap();
+        assert_eq!(vec!["alpha", "charlie", "echo"], value.tokens);
     }
 
     #[test]
-    fn change_block_count() {
+    fn num_and_all_tokens() {
         let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
 
         let msg = int_mgs();
-        let info = mock_info("creator", &coins(2, "token"));
+        let info = mock_info("creator", &coins(1000, "ujuno"));
         let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-        // beneficiary can release it
-        let info = mock_info("creator", &coins(2, "token"));
-        let msg = ExecuteMsg::SetBlocksPerYear { blocks_per_year: 5048094 };
-        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::NumTokens {}).unwrap();
+        let value: NumTokensResponse = from_binary(&res).unwrap();
+        assert_eq!(0, value.tokens);
 
-        // should increase counter by 1
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetBlocksPerYear {}).unwrap();
-        let value: BlocksResponse = from_binary(&res).unwrap();
-        assert_eq!(5048094, value.blocks_per_year);
+        for (name, owner) in [("delta", "annie"), ("alpha", "bobby"), ("charlie", "annie")] {
+            let auth_info = mock_info(owner, &coins(200000, "ujuno"));
+            let msg = ExecuteMsg::RegisterName { name: String::from(name) , years: 1 , records: None, addresses: None};
+            let _res = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+        }
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::NumTokens {}).unwrap();
+        let value: NumTokensResponse = from_binary(&res).unwrap();
+        assert_eq!(3, value.tokens);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::AllTokens { start_after: None, limit: Some(2) }).unwrap();
+        let value: TokensResponse = from_binary(&res).unwrap();
+        assert_eq!(vec!["alpha", "charlie"], value.tokens);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::AllTokens { start_after: Some(String::from("charlie")), limit: Some(2) }).unwrap();
+        let value: TokensResponse = from_binary(&res).unwrap();
+        assert_eq!(vec!["delta"], value.tokens);
+
+        // picking up an expired name reuses its token
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(SECONDS_IN_YEAR + 1);
+        let auth_info = mock_info("bobby", &coins(200000, "ujuno"));
+        let msg = ExecuteMsg::RegisterName { name: String::from("delta") , years: 1 , records: None, addresses: None};
+        let _res = execute(deps.as_mut(), env.clone(), auth_info, msg).unwrap();
+
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::NumTokens {}).unwrap();
+        let value: NumTokensResponse = from_binary(&res).unwrap();
+        assert_eq!(3, value.tokens);
+
+        let auth_info = mock_info("bobby", &coins(200000, "ujuno"));
+        let msg = ExecuteMsg::RegisterName { name: String::from("echo") , years: 1 , records: None, addresses: None};
+        let _res = execute(deps.as_mut(), env.clone(), auth_info, msg).unwrap();
+
+        let res = query(deps.as_ref(), env, QueryMsg::NumTokens {}).unwrap();
+        let value: NumTokensResponse = from_binary(&res).unwrap();
+        assert_eq!(4, value.tokens);
     }
 
     #[test]
-    fn change_owner() {
+    fn grace_and_redemption() {
         let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
 
-        let msg = int_mgs();
-        let info = mock_info("creator", &coins(2, "token"));
+        const DAY: u64 = 24 * 60 * 60;
+        let mut msg = int_mgs();
+        msg.grace_period = Some(30 * DAY);
+        msg.redemption_period = Some(10 * DAY);
+        msg.redemption_fee = Some(Uint128::new(100));
+        let info = mock_info("creator", &coins(1000, "ujuno"));
         let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-        // beneficiary can release it
-        let unauth_info = mock_info("anyone", &coins(2, "token"));
-        let msg = ExecuteMsg::SetOwner { owner: Addr::unchecked("anyone") };
-        let res = execute(deps.as_mut(), mock_env(), unauth_info, msg);
-        match res {
-            Err(ContractError::Unauthorized {}) => {}
-            _ => panic!("Must return unauthorized error"),
+        let register = |name: &str| ExecuteMsg::RegisterName { name: String::from(name) , years: 1 , records: None, addresses: None};
+        for name in ["graceful", "redeemed", "released"] {
+            let auth_info = mock_info("annie", &coins(1, "ujuno"));
+            let _res = execute(deps.as_mut(), mock_env(), auth_info, register(name)).unwrap();
         }
 
-        // only the original creator can reset the counter
-        let auth_info = mock_info("creator", &coins(2, "token"));
-        let msg = ExecuteMsg::SetOwner { owner: Addr::unchecked("anyone") };
-        let _res = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+        let status = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, env: Env, name: &str| -> NameStatusResponse {
+            let res = query(deps.as_ref(), env, QueryMsg::NameStatus { name: String::from(name) }).unwrap();
+            from_binary(&res).unwrap()
+        };
 
-        // should now be 5
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetOwner {}).unwrap();
+        let res = status(&deps, mock_env(), "graceful");
+        assert_eq!(NameStatus::Active, res.status);
+        assert_eq!(Some(Addr::unchecked("annie")), res.owner);
+        let res = status(&deps, mock_env(), "unknown");
+        assert_eq!(NameStatus::Available, res.status);
+        assert_eq!(None, res.expires);
+
+        // grace period: nobody else can take it, queries say why it does not resolve
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(SECONDS_IN_YEAR + DAY);
+        assert_eq!(NameStatus::Grace, status(&deps, env.clone(), "graceful").status);
+
+        let err = query(deps.as_ref(), env.clone(), QueryMsg::ResolveName { name: String::from("graceful") }).unwrap_err();
+        assert!(err.to_string().contains("grace period"));
+        let err = query(deps.as_ref(), env.clone(), QueryMsg::NftInfo { token_id: String::from("graceful") }).unwrap_err();
+        assert!(err.to_string().contains("grace period"));
+
+        let auth_info = mock_info("bobby", &coins(200000, "ujuno"));
+        assert!(matches!(execute(deps.as_mut(), env.clone(), auth_info, register("graceful")), Err(ContractError::InRenewalPeriod { .. })));
+        let auth_info = mock_info("bobby", &coins(200000, "ujuno"));
+        let msg = ExecuteMsg::AddTime { name: String::from("graceful"), years: 1 };
+        assert!(matches!(execute(deps.as_mut(), env.clone(), auth_info, msg.clone()), Err(ContractError::Unauthorized { .. })));
+
+        // the owner renews at the normal price
+        let auth_info = mock_info("annie", &coins(1, "ujuno"));
+        let _res = execute(deps.as_mut(), env.clone(), auth_info, msg).unwrap();
+        assert_eq!(NameStatus::Active, status(&deps, env.clone(), "graceful").status);
+        let res = query(deps.as_ref(), env, QueryMsg::ResolveName { name: String::from("graceful") }).unwrap();
         let value: OwnerResponse = from_binary(&res).unwrap();
-        assert_eq!(Addr::unchecked("anyone"), value.owner);
+        assert_eq!(Addr::unchecked("annie"), value.owner);
+
+        // redemption: renewing costs the fee on top
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(SECONDS_IN_YEAR + 35 * DAY);
+        assert_eq!(NameStatus::Redemption, status(&deps, env.clone(), "redeemed").status);
+        let err = query(deps.as_ref(), env.clone(), QueryMsg::ResolveAttributes { name: String::from("redeemed") }).unwrap_err();
+        assert!(err.to_string().contains("redemption period"));
+
+        let msg = ExecuteMsg::AddTime { name: String::from("redeemed"), years: 0 };
+        let auth_info = mock_info("annie", &coins(101, "ujuno"));
+        assert!(matches!(execute(deps.as_mut(), env.clone(), auth_info, msg), Err(ContractError::NoYears {})));
+
+        let msg = ExecuteMsg::AddTime { name: String::from("redeemed"), years: 1 };
+        let auth_info = mock_info("annie", &coins(1, "ujuno"));
+        assert!(matches!(execute(deps.as_mut(), env.clone(), auth_info, msg.clone()), Err(ContractError::InsufficientFunds { .. })));
+        let auth_info = mock_info("annie", &coins(101, "ujuno"));
+        let _res = execute(deps.as_mut(), env, auth_info, msg).unwrap();
+
+        // afterwards the name is released to anyone
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(SECONDS_IN_YEAR + 41 * DAY);
+        assert_eq!(NameStatus::Available, status(&deps, env.clone(), "released").status);
+
+        let auth_info = mock_info("annie", &coins(200000, "ujuno"));
+        let msg = ExecuteMsg::AddTime { name: String::from("released"), years: 1 };
+        assert!(matches!(execute(deps.as_mut(), env.clone(), auth_info, msg), Err(ContractError::Expired { .. })));
+
+        let auth_info = mock_info("bobby", &coins(1, "ujuno"));
+        let _res = execute(deps.as_mut(), env.clone(), auth_info, register("released")).unwrap();
+        let res = status(&deps, env, "released");
+        assert_eq!(NameStatus::Active, res.status);
+        assert_eq!(Some(Addr::unchecked("bobby")), res.owner);
     }
 
     #[test]
-    fn register_name() {
+    fn zero_year_registration() {
         let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
 
-        let msg = int_mgs();
         let info = mock_info("creator", &coins(1000, "ujuno"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, int_mgs()).unwrap();
 
-        // we can just call .unwrap() to assert this was a success
-        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
-
-        let auth_info = mock_info("annie", &coins(200000, "ujuno"));
-        let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years: 2 , avatar_url: None, terra_address: None, secret_address: None, crypto_org_address: None, kava_address: None, persistence_address: None, starname_address: None, website: None, email: None, twitter: None, telegram: None, discord: None, instagram: None, reddit: None};
-        let res1 = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+        let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years: 0 , records: None, addresses: None};
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("annie", &[]), msg), Err(ContractError::NoYears {})));
 
-        let auth_info = mock_info("bobby", &coins(200000, "ujuno"));
-        let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years: 3 , avatar_url: None, terra_address: None, secret_address: None, crypto_org_address: None, kava_address: None, persistence_address: None, starname_address: None, website: None, email: None, twitter: None, telegram: None, discord: None, instagram: None, reddit: None};
-        let res2 = execute(deps.as_mut(), mock_env(), auth_info, msg);
-        
-        assert_eq!(res2.is_err(), true);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::NumTokens {}).unwrap();
+        let value: NumTokensResponse = from_binary(&res).unwrap();
+        assert_eq!(0, value.tokens);
+    }
 
-        println!("{:?}", res1);
-        println!("{:?}", res2);
+    #[test]
+    fn commitment_ages() {
+        // a commitment that expires before it can be revealed locks every name
+        let mut msg = int_mgs();
+        msg.require_commitment = Some(true);
+        msg.min_commitment_age = Some(100);
+        msg.max_commitment_age = Some(10);
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+        assert!(matches!(instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg), Err(ContractError::InvalidConfig { .. })));
     }
 
     #[test]
-    fn add_time_to_name() {
+    fn commitments_are_pruned() {
         let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
 
-        let msg = int_mgs();
         let info = mock_info("creator", &coins(1000, "ujuno"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, int_mgs()).unwrap();
 
-        // we can just call .unwrap() to assert this was a success
-        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
+        let commit = |name: &str| ExecuteMsg::CommitName { commitment: make_commitment(name, &Addr::unchecked("annie"), "hunter2") };
+        for name in ["first", "second"] {
+            let res = execute(deps.as_mut(), mock_env(), mock_info("annie", &[]), commit(name)).unwrap();
+            assert!(res.attributes.iter().any(|a| a.key == "commitments_pruned" && a.value == "0"));
+        }
+
+        // the next commit after they expired clears both out
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(DEFAULT_MAX_COMMITMENT_AGE + 1);
+        let res = execute(deps.as_mut(), env.clone(), mock_info("annie", &[]), commit("third")).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "commitments_pruned" && a.value == "2"));
+
+        let remaining: Vec<String> = commitments().keys(deps.as_ref().storage, None, None, Order::Ascending).collect::<StdResult<_>>().unwrap();
+        assert_eq!(vec![make_commitment("third", &Addr::unchecked("annie"), "hunter2")], remaining);
+    }
+
+    #[test]
+    fn commit_reveal() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let mut msg = int_mgs();
+        msg.require_commitment = Some(true);
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
+        // plain registration is switched off
         let auth_info = mock_info("annie", &coins(200000, "ujuno"));
-        let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years: 2 , avatar_url: None, terra_address: None, secret_address: None, crypto_org_address: None, kava_address: None, persistence_address: None, starname_address: None, website: None, email: None, twitter: None, telegram: None, discord: None, instagram: None, reddit: None};
-        let res1 = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+        let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years: 1 , records: None, addresses: None};
+        assert!(matches!(execute(deps.as_mut(), mock_env(), auth_info, msg), Err(ContractError::CommitmentRequired { .. })));
+
+        let commitment = make_commitment("testname", &Addr::unchecked("annie"), "hunter2");
+        let auth_info = mock_info("annie", &[]);
+        let _res = execute(deps.as_mut(), mock_env(), auth_info, ExecuteMsg::CommitName { commitment: commitment.clone() }).unwrap();
+
+        // can't be refreshed while pending
+        let auth_info = mock_info("bobby", &[]);
+        assert!(matches!(execute(deps.as_mut(), mock_env(), auth_info, ExecuteMsg::CommitName { commitment }), Err(ContractError::CommitmentExists { .. })));
+
+        let reveal = |secret: &str| ExecuteMsg::RevealName { name: String::from("TestName"), secret: String::from(secret), years: 1 , records: None, addresses: None};
 
+        // too young
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(DEFAULT_MIN_COMMITMENT_AGE - 1);
         let auth_info = mock_info("annie", &coins(200000, "ujuno"));
-        let msg = ExecuteMsg::AddTime { name: String::from("testname") , years: 2 };
-        let res2 = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+        let res = execute(deps.as_mut(), env.clone(), auth_info, reveal("hunter2"));
+        assert!(matches!(res, Err(ContractError::CommitmentTooNew {})));
 
+        env.block.time = env.block.time.plus_seconds(1);
+
+        // wrong secret or a different sender do not match
+        let auth_info = mock_info("annie", &coins(200000, "ujuno"));
+        assert!(matches!(execute(deps.as_mut(), env.clone(), auth_info, reveal("hunter3")), Err(ContractError::CommitmentNotFound { .. })));
         let auth_info = mock_info("bobby", &coins(200000, "ujuno"));
-        let msg = ExecuteMsg::AddTime { name: String::from("testname") , years: 2 };
-        let res3 = execute(deps.as_mut(), mock_env(), auth_info, msg);
-        assert_eq!(res3.is_err(), true);
-        
-        println!("{:?}", res1);
-        println!("{:?}", res2);
-        println!("{:?}", res3);
+        assert!(matches!(execute(deps.as_mut(), env.clone(), auth_info, reveal("hunter2")), Err(ContractError::CommitmentNotFound { .. })));
+
+        let auth_info = mock_info("annie", &coins(200000, "ujuno"));
+        let _res = execute(deps.as_mut(), env.clone(), auth_info, reveal("hunter2")).unwrap();
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::ResolveName { name: String::from("testname") }).unwrap();
+        let value: OwnerResponse = from_binary(&res).unwrap();
+        assert_eq!(Addr::unchecked("annie"), value.owner);
+
+        // the commitment is spent
+        let auth_info = mock_info("annie", &coins(200000, "ujuno"));
+        assert!(matches!(execute(deps.as_mut(), env.clone(), auth_info, reveal("hunter2")), Err(ContractError::CommitmentNotFound { .. })));
+
+        // revealing still needs at least a year
+        let commitment = make_commitment("othername", &Addr::unchecked("annie"), "hunter2");
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("annie", &[]), ExecuteMsg::CommitName { commitment }).unwrap();
+        env.block.time = env.block.time.plus_seconds(DEFAULT_MIN_COMMITMENT_AGE);
+        let msg = ExecuteMsg::RevealName { name: String::from("othername"), secret: String::from("hunter2"), years: 0 , records: None, addresses: None};
+        assert!(matches!(execute(deps.as_mut(), env, mock_info("annie", &coins(200000, "ujuno")), msg), Err(ContractError::NoYears {})));
     }
 
     #[test]
-    fn resolve_name() {
+    fn stale_commitment() {
         let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
 
-        let msg = int_mgs();
         let info = mock_info("creator", &coins(1000, "ujuno"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, int_mgs()).unwrap();
 
-        // we can just call .unwrap() to assert this was a success
-        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
+        let commitment = make_commitment("testname", &Addr::unchecked("annie"), "hunter2");
+        let auth_info = mock_info("annie", &[]);
+        let _res = execute(deps.as_mut(), mock_env(), auth_info, ExecuteMsg::CommitName { commitment: commitment.clone() }).unwrap();
 
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(DEFAULT_MAX_COMMITMENT_AGE + 1);
         let auth_info = mock_info("annie", &coins(200000, "ujuno"));
-        let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years: 2 , avatar_url: None, terra_address: None, secret_address: None, crypto_org_address: None, kava_address: None, persistence_address: None, starname_address: None, website: None, email: None, twitter: None, telegram: None, discord: None, instagram: None, reddit: None};
-        let _res = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+        let msg = ExecuteMsg::RevealName { name: String::from("testname"), secret: String::from("hunter2"), years: 1 , records: None, addresses: None};
+        let res = execute(deps.as_mut(), env.clone(), auth_info, msg);
+        assert!(matches!(res, Err(ContractError::CommitmentExpired {})));
 
-        
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::ResolveName { name : String::from("testname")}).unwrap();
-        let value: OwnerResponse = from_binary(&res).unwrap();
-        assert_eq!(Addr::unchecked("annie"), value.owner);
+        // an expired commitment can be made again
+        let auth_info = mock_info("annie", &[]);
+        let _res = execute(deps.as_mut(), env, auth_info, ExecuteMsg::CommitName { commitment }).unwrap();
 
+        let auth_info = mock_info("annie", &[]);
+        assert!(matches!(execute(deps.as_mut(), mock_env(), auth_info, ExecuteMsg::CommitName { commitment: String::from("not a hash") }), Err(ContractError::InvalidCommitment { .. })));
     }
 
     #[test]
-    fn request_name() {
+    fn invalid_names() {
         let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
 
-        let msg = int_mgs();
         let info = mock_info("creator", &coins(1000, "ujuno"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, int_mgs()).unwrap();
+
+        for name in ["bad name", "zero\u{200b}width", "-dash", "j\u{430}ckal"] {
+            let auth_info = mock_info("annie", &coins(200000, "ujuno"));
+            let msg = ExecuteMsg::RegisterName { name: String::from(name) , years: 1 , records: None, addresses: None};
+            match execute(deps.as_mut(), mock_env(), auth_info, msg) {
+                Err(ContractError::InvalidName { .. }) => {}
+                res => panic!("Must reject {:?}, got {:?}", name, res),
+            }
+        }
 
-        // we can just call .unwrap() to assert this was a success
-        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
-
+        // mixed case is the same name everywhere
         let auth_info = mock_info("annie", &coins(200000, "ujuno"));
-        let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years: 2 , avatar_url: None, terra_address: None, secret_address: None, crypto_org_address: None, kava_address: None, persistence_address: None, starname_address: None, website: None, email: None, twitter: None, telegram: None, discord: None, instagram: None, reddit: None};
+        let msg = ExecuteMsg::RegisterName { name: String::from("JackalLabs") , years: 1 , records: None, addresses: None};
         let _res = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
 
-        
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::ResolveAttributes { name : String::from("testname")}).unwrap();
-        let value: NameResponse = from_binary(&res).unwrap();
-        assert_eq!(Name {id: String::from("testname") , expires: 1571797419879305533 + Timestamp::from_seconds(SECONDS_IN_YEAR * 2).nanos() , owner: Addr::unchecked("annie"), approvals: vec![], avatar_url: None, terra_address: None, secret_address: None, crypto_org_address: None, kava_address: None, persistence_address: None, starname_address: None, website: None, email: None, twitter: None, telegram: None, discord: None, instagram: None, reddit: None}, value.name);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::OwnerOf { token_id: String::from("JACKALLABS") }).unwrap();
+        let value: OwnerResponse = from_binary(&res).unwrap();
+        assert_eq!(Addr::unchecked("annie"), value.owner);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ResolveName { name: String::from("jackal labs") });
+        assert!(res.is_err());
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::PriceQuote { name: String::from("jackal.labs"), years: 1 });
+        assert!(res.is_err());
 
+        let annie = mock_info("annie", &[]);
+        let transfer_msg = ExecuteMsg::TransferNft { recipient: "carl".to_string(), token_id: "jackallabs\u{200b}".to_string(), keep_records: None };
+        match execute(deps.as_mut(), mock_env(), annie, transfer_msg) {
+            Err(ContractError::InvalidName { .. }) => {}
+            res => panic!("Must reject the token id, got {:?}", res),
+        }
     }
 
     #[test]
@@ -997,7 +4158,7 @@ mod tests {
         let token_id = "melt".to_string();
 
         let auth_info = mock_info("annie", &coins(200000, "ujuno"));
-        let msg = ExecuteMsg::RegisterName { name: token_id.clone() , years: 2 , avatar_url: None, terra_address: None, secret_address: None, crypto_org_address: None, kava_address: None, persistence_address: None, starname_address: None, website: None, email: None, twitter: None, telegram: None, discord: None, instagram: None, reddit: None};
+        let msg = ExecuteMsg::RegisterName { name: token_id.clone() , years: 2 , records: None, addresses: None};
         let _res1 = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
 
         // random cannot transfer
@@ -1005,21 +4166,31 @@ mod tests {
         let transfer_msg = ExecuteMsg::TransferNft {
             recipient: "random".into(),
             token_id: token_id.clone(),
+            keep_records: None,
         };
 
-        let _err = execute(deps.as_mut(), mock_env(), random, transfer_msg.clone()).unwrap_err();
+        let res = execute(deps.as_mut(), mock_env(), random, transfer_msg.clone());
+        assert!(matches!(res, Err(ContractError::Unauthorized {})));
 
+        // nor can anything go to an invalid address or move a missing name
+        let msg = ExecuteMsg::TransferNft { recipient: "x".into(), token_id: token_id.clone(), keep_records: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("annie", &[]), msg);
+        assert!(matches!(res, Err(ContractError::InvalidRecipient { .. })));
+        let msg = ExecuteMsg::TransferNft { recipient: "random".into(), token_id: "missing".into(), keep_records: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("annie", &[]), msg);
+        assert!(matches!(res, Err(ContractError::NameNotFound { .. })));
 
         // owner can
         let success = mock_info("annie", &coins(200000, "ujuno"));
         let transfer_msg = ExecuteMsg::TransferNft {
             recipient: "random".into(),
             token_id: token_id.clone(),
+            keep_records: None,
         };
 
         let res = execute(deps.as_mut(), mock_env(), success, transfer_msg.clone());
 
-        assert_eq!(res.is_err(), false);
+        assert!(res.is_ok());
     }
 
 
@@ -1038,12 +4209,13 @@ mod tests {
         let token_id = "melt".to_string();
 
         let auth_info = mock_info("annie", &coins(200000, "ujuno"));
-        let msg = ExecuteMsg::RegisterName { name: token_id.clone() , years: 2 , avatar_url: None, terra_address: None, secret_address: None, crypto_org_address: None, kava_address: None, persistence_address: None, starname_address: None, website: None, email: None, twitter: None, telegram: None, discord: None, instagram: None, reddit: None};
+        let msg = ExecuteMsg::RegisterName { name: token_id.clone() , years: 2 , records: None, addresses: None};
         let _res1 = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
 
         let transfer_msg = ExecuteMsg::TransferNft {
             recipient: "carl".to_string(),
             token_id: token_id.clone(),
+            keep_records: None,
         };
 
         let approved = mock_info("bobby", &coins(200000, "ujuno"));
@@ -1061,12 +4233,13 @@ mod tests {
 
         let res = execute(deps.as_mut(), mock_env(), owner, approve_msg);
         
-        assert_eq!(res.is_err(), false);
+        assert!(res.is_ok());
 
 
         let transfer_msg = ExecuteMsg::TransferNft {
             recipient: "carl".to_string(),
             token_id: token_id.clone(),
+            keep_records: None,
         };
 
         let approved = mock_info("bobby", &coins(200000, "ujuno"));
@@ -1076,4 +4249,269 @@ mod tests {
 
     }
 
+
+    fn legacy_name(id: &str, owner: &str) -> migrations::v0_1_0::Name {
+        migrations::v0_1_0::Name {
+            id: id.to_string(),
+            expires: mock_env().block.time.plus_seconds(SECONDS_IN_YEAR).nanos(),
+            owner: Addr::unchecked(owner),
+            approvals: vec![],
+            avatar_url: Some("example.com/avatar.png".to_string()),
+            secret_address: None,
+            crypto_org_address: None,
+            starname_address: None,
+            persistence_address: None,
+            kava_address: None,
+            terra_address: Some("terra1annie".to_string()),
+            website: None,
+            email: None,
+            twitter: Some("@annie".to_string()),
+            telegram: None,
+            discord: None,
+            instagram: None,
+            reddit: None,
+        }
+    }
+
+    fn legacy_state() -> migrations::v0_1_0::State {
+        migrations::v0_1_0::State {
+            blocks_per_year: 5048093,
+            owner: Addr::unchecked("creator"),
+            meta_url: "example.com".to_string(),
+            denom: "ujuno".to_string(),
+            cost_for_6: 1,
+            cost_for_5: 2,
+            cost_for_4: 4,
+            cost_for_3: 8,
+            cost_for_2: 16,
+            cost_for_1: 32,
+        }
+    }
+
+    #[test]
+    fn migrate_from_v0_1_0() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        // populate the store the way a v0.1.0 deployment left it
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+        migrations::v0_1_0::STATE.save(deps.as_mut().storage, &legacy_state()).unwrap();
+        migrations::v0_1_0::JNS.save(deps.as_mut().storage, "annie", &legacy_name("annie", "annie")).unwrap();
+        migrations::v0_1_0::JNS.save(deps.as_mut().storage, "bobby", &legacy_name("bobby", "bobby")).unwrap();
+
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: None }).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "names_migrated" && a.value == "2"));
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap();
+        let value: State = from_binary(&res).unwrap();
+        assert_eq!(6, value.prices.len());
+        assert_eq!(PriceTier { min_length: 1, cost: Uint128::new(32) }, value.prices[0]);
+        assert_eq!(PriceTier { min_length: 6, cost: Uint128::new(1) }, value.prices[5]);
+
+        let version = get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(CONTRACT_VERSION, version.version);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetOwner {}).unwrap();
+        let value: OwnerResponse = from_binary(&res).unwrap();
+        assert_eq!(Addr::unchecked("creator"), value.owner);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ResolveAttributes { name: String::from("bobby") }).unwrap();
+        let value: NameResponse = from_binary(&res).unwrap();
+        assert_eq!(Addr::unchecked("bobby"), value.name.owner);
+        assert_eq!(vec![ChainAddress { chain: String::from("terra"), address: String::from("terra1annie") }], value.name.addresses);
+
+        // the old profile fields are carried over as text records
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetRecords { name: String::from("bobby"), keys: None }).unwrap();
+        let value: RecordsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![
+            TextRecord { key: String::from("avatar"), value: String::from("example.com/avatar.png") },
+            TextRecord { key: String::from("com.twitter"), value: String::from("@annie") },
+        ], value.records);
+
+        // the owner index is built for migrated names
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Tokens { owner: String::from("bobby"), start_after: None, limit: None, include_expired: None }).unwrap();
+        let value: TokensResponse = from_binary(&res).unwrap();
+        assert_eq!(vec!["bobby"], value.tokens);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::NumTokens {}).unwrap();
+        let value: NumTokensResponse = from_binary(&res).unwrap();
+        assert_eq!(2, value.tokens);
+
+        // migrated names keep working with the current handlers
+        let auth_info = mock_info("annie", &coins(200000, "ujuno"));
+        let msg = ExecuteMsg::AddTime { name: String::from("annie"), years: 1 };
+        execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+    }
+
+    #[test]
+    fn migrate_quarantines_dotted_names() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+        migrations::v0_1_0::STATE.save(deps.as_mut().storage, &legacy_state()).unwrap();
+        migrations::v0_1_0::JNS.save(deps.as_mut().storage, "bar", &legacy_name("bar", "annie")).unwrap();
+        migrations::v0_1_0::JNS.save(deps.as_mut().storage, "foo.bar", &legacy_name("foo.bar", "carl")).unwrap();
+
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: None }).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "names_migrated" && a.value == "1"));
+        let quarantined: Vec<&str> = res.attributes.iter().filter(|a| a.key == "quarantined").map(|a| a.value.as_str()).collect();
+        assert_eq!(vec!["\"foo.bar\""], quarantined);
+
+        // it was never a subdomain of bar, so bar's owner gets no hold on it
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Subdomains { parent: String::from("bar"), start_after: None, limit: None }).unwrap();
+        let value: TokensResponse = from_binary(&res).unwrap();
+        assert!(value.tokens.is_empty());
+        let msg = ExecuteMsg::RevokeSubdomain { name: String::from("foo.bar") };
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("annie", &[]), msg), Err(ContractError::NameNotFound { .. })));
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::NumTokens {}).unwrap();
+        let value: NumTokensResponse = from_binary(&res).unwrap();
+        assert_eq!(1, value.tokens);
+
+        let kept = migrations::QUARANTINED.load(deps.as_ref().storage, "foo.bar").unwrap();
+        assert_eq!(Addr::unchecked("carl"), kept.owner);
+    }
+
+    #[test]
+    fn migrate_quarantines_invalid_names() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+        migrations::v0_1_0::STATE.save(deps.as_mut().storage, &legacy_state()).unwrap();
+        for id in ["hello world", "", "caf\u{e9}", "annie"] {
+            migrations::v0_1_0::JNS.save(deps.as_mut().storage, id, &legacy_name(id, "annie")).unwrap();
+        }
+
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: None }).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "names_migrated" && a.value == "1"));
+        let quarantined: Vec<&str> = res.attributes.iter().filter(|a| a.key == "quarantined").map(|a| a.value.as_str()).collect();
+        assert_eq!(vec!["\"\"", "\"caf\u{e9}\"", "\"hello world\""], quarantined);
+
+        // only the name the current handlers can reach is a token
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::NumTokens {}).unwrap();
+        let value: NumTokensResponse = from_binary(&res).unwrap();
+        assert_eq!(1, value.tokens);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Tokens { owner: String::from("annie"), start_after: None, limit: None, include_expired: None }).unwrap();
+        let value: TokensResponse = from_binary(&res).unwrap();
+        assert_eq!(vec!["annie"], value.tokens);
+
+        let kept = migrations::QUARANTINED.load(deps.as_ref().storage, "hello world").unwrap();
+        assert_eq!(Addr::unchecked("annie"), kept.owner);
+        assert!(jns().may_load(deps.as_ref().storage, "hello world").unwrap().is_none());
+
+        // they can be paged through by their raw ids
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Quarantined { start_after: None, limit: Some(2) }).unwrap();
+        let value: QuarantinedResponse = from_binary(&res).unwrap();
+        assert_eq!(vec!["", "caf\u{e9}"], value.names.iter().map(|n| n.id.as_str()).collect::<Vec<_>>());
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Quarantined { start_after: Some(String::from("caf\u{e9}")), limit: None }).unwrap();
+        let value: QuarantinedResponse = from_binary(&res).unwrap();
+        assert_eq!(vec!["hello world"], value.names.iter().map(|n| n.id.as_str()).collect::<Vec<_>>());
+
+        // the contract owner gives one back under a valid name, keeping its records
+        let msg = ExecuteMsg::ReleaseQuarantined { id: String::from("hello world"), name: Some(String::from("hello-world")) };
+        match execute(deps.as_mut(), mock_env(), mock_info("annie", &[]), msg.clone()) {
+            Err(ContractError::Unauthorized {}) => {}
+            res => panic!("Must return unauthorized error, got {:?}", res),
+        }
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg.clone()).unwrap();
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg), Err(ContractError::NameNotFound { .. })));
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ResolveAttributes { name: String::from("hello-world") }).unwrap();
+        let value: NameResponse = from_binary(&res).unwrap();
+        assert_eq!(Addr::unchecked("annie"), value.name.owner);
+        assert_eq!(kept.expires, value.name.expires);
+        assert_eq!(kept.records, value.name.records);
+
+        // taken names can't be handed out this way, and the rest can simply be dropped
+        let msg = ExecuteMsg::ReleaseQuarantined { id: String::from(""), name: Some(String::from("annie")) };
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg), Err(ContractError::NameTaken { .. })));
+        let msg = ExecuteMsg::ReleaseQuarantined { id: String::from(""), name: None };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::NumTokens {}).unwrap();
+        let value: NumTokensResponse = from_binary(&res).unwrap();
+        assert_eq!(2, value.tokens);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Quarantined { start_after: None, limit: None }).unwrap();
+        let value: QuarantinedResponse = from_binary(&res).unwrap();
+        assert_eq!(vec!["caf\u{e9}"], value.names.iter().map(|n| n.id.as_str()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn migrate_in_batches() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+        migrations::v0_1_0::STATE.save(deps.as_mut().storage, &legacy_state()).unwrap();
+        for id in ["annie", "bobby", "carl", "hello world"] {
+            migrations::v0_1_0::JNS.save(deps.as_mut().storage, id, &legacy_name(id, "annie")).unwrap();
+        }
+
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: Some(2) }).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "names_migrated" && a.value == "2"));
+        assert!(res.attributes.iter().any(|a| a.key == "migration_done" && a.value == "false"));
+        assert_eq!("0.1.0", get_contract_version(deps.as_ref().storage).unwrap().version);
+
+        // nothing runs against the half migrated store
+        let msg = ExecuteMsg::AddTime { name: String::from("annie"), years: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("annie", &coins(200000, "ujuno")), msg);
+        assert!(matches!(res, Err(ContractError::MigrationInProgress {})));
+
+        // the next call carries on after the cursor and leaves the config alone
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: Some(2) }).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "names_migrated" && a.value == "1"));
+        assert!(res.attributes.iter().any(|a| a.key == "quarantined" && a.value == "\"hello world\""));
+        assert!(res.attributes.iter().any(|a| a.key == "migration_done" && a.value == "true"));
+        assert_eq!(CONTRACT_VERSION, get_contract_version(deps.as_ref().storage).unwrap().version);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap();
+        let value: State = from_binary(&res).unwrap();
+        assert_eq!(PriceTier { min_length: 1, cost: Uint128::new(32) }, value.prices[0]);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::NumTokens {}).unwrap();
+        let value: NumTokensResponse = from_binary(&res).unwrap();
+        assert_eq!(3, value.tokens);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Tokens { owner: String::from("annie"), start_after: None, limit: None, include_expired: None }).unwrap();
+        let value: TokensResponse = from_binary(&res).unwrap();
+        assert_eq!(vec!["annie", "bobby", "carl"], value.tokens);
+
+        let msg = ExecuteMsg::AddTime { name: String::from("annie"), years: 1 };
+        execute(deps.as_mut(), mock_env(), mock_info("annie", &coins(200000, "ujuno")), msg).unwrap();
+    }
+
+    #[test]
+    fn migrate_refuses_downgrade() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        instantiate(deps.as_mut(), mock_env(), info, int_mgs()).unwrap();
+
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "99.0.0").unwrap();
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: None });
+        match res {
+            Err(ContractError::CannotDowngrade { .. }) => {}
+            _ => panic!("Must refuse to downgrade"),
+        }
+
+        cw2::set_contract_version(deps.as_mut().storage, "crates.io:cw721-base", "0.1.0").unwrap();
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: None });
+        match res {
+            Err(ContractError::CannotMigrate { .. }) => {}
+            _ => panic!("Must refuse a foreign contract"),
+        }
+    }
+
+    #[test]
+    fn migrate_same_version() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        instantiate(deps.as_mut(), mock_env(), info, int_mgs()).unwrap();
+
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: None }).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "names_migrated" && a.value == "0"));
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetBlocksPerYear {}).unwrap();
+        let value: BlocksResponse = from_binary(&res).unwrap();
+        assert_eq!(5048093, value.blocks_per_year);
+    }
+
 }
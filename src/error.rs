@@ -1,4 +1,5 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{OverflowError, StdError, Uint128};
+use cw_utils::PaymentError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -6,8 +7,125 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("{0}")]
+    Overflow(#[from] OverflowError),
+
     #[error("Unauthorized")]
     Unauthorized {},
+
+    #[error("Invalid config: {reason}")]
+    InvalidConfig { reason: String },
+
+    #[error("Name {name} is not registered")]
+    NameNotFound { name: String },
+
+    #[error("Name {name} is already registered")]
+    NameTaken { name: String },
+
+    #[error("Name {name} expired but can still be renewed by its owner")]
+    InRenewalPeriod { name: String },
+
+    #[error("Name {name} expired and must be registered again")]
+    Expired { name: String },
+
+    #[error("Name {name} is reserved")]
+    NameReserved { name: String },
+
+    #[error("Name {name} is not reserved")]
+    NotReserved { name: String },
+
+    #[error("Names must be registered for at least a year")]
+    NoYears {},
+
+    #[error("Names can't be registered more than {max_years} years ahead")]
+    TooFarAhead { max_years: u64 },
+
+    #[error("Expiration is already in the past")]
+    AlreadyExpired {},
+
+    #[error("Not enough {denom} sent, wanted {required}")]
+    InsufficientFunds { required: Uint128, denom: String },
+
+    #[error("Unsupported denom {denom}")]
+    UnsupportedDenom { denom: String },
+
+    #[error("Invalid recipient {recipient:?}")]
+    InvalidRecipient { recipient: String },
+
+    #[error("Invalid record {key:?}: {reason}")]
+    InvalidRecord { key: String, reason: String },
+
+    #[error("Record {key} not found")]
+    RecordNotFound { key: String },
+
+    #[error("A name can hold at most {max} records")]
+    TooManyRecords { max: usize },
+
+    #[error("A name can hold at most {max} addresses")]
+    TooManyAddresses { max: usize },
+
+    #[error("{name} is not a subdomain")]
+    NotSubdomain { name: String },
+
+    #[error("Subdomain {name} was sold and can't be revoked before it expires")]
+    SubdomainSold { name: String },
+
+    #[error("Subdomains of {parent} are not for sale")]
+    NotForSale { parent: String },
+
+    #[error("Name {name} is being auctioned")]
+    NameInAuction { name: String },
+
+    #[error("No auction for {name}")]
+    AuctionNotFound { name: String },
+
+    #[error("Auction for {name} has ended")]
+    AuctionEnded { name: String },
+
+    #[error("Auction for {name} is still running")]
+    AuctionRunning { name: String },
+
+    #[error("Bid too low, wanted at least {required}")]
+    BidTooLow { required: Uint128 },
+
+    #[error("Names must be registered with CommitName and RevealName")]
+    CommitmentRequired {},
+
+    #[error("Commitment must be a hex encoded sha256 hash")]
+    InvalidCommitment {},
+
+    #[error("Commitment already exists")]
+    CommitmentExists {},
+
+    #[error("Commitment does not match")]
+    CommitmentNotFound {},
+
+    #[error("Commitment is too new to reveal")]
+    CommitmentTooNew {},
+
+    #[error("Commitment has expired")]
+    CommitmentExpired {},
+
+    #[error("Cannot migrate from a different contract: {previous_contract}")]
+    CannotMigrate { previous_contract: String },
+
+    #[error("Cannot migrate from version {stored} down to {current}")]
+    CannotDowngrade { stored: String, current: String },
+
+    #[error("A migration is still in progress, migrate again to finish it")]
+    MigrationInProgress {},
+
+    #[error("Invalid name {name:?}: {reason}")]
+    InvalidName { name: String, reason: String },
+
+    #[error("Invalid address {address:?}: {reason}")]
+    InvalidAddress { address: String, reason: String },
+
+    #[error("Invalid contract version: {version}")]
+    InvalidVersion { version: String },
     // Add any other custom errors you like here.
     // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
 }
@@ -16,7 +16,7 @@ mod tests {
 
     const USER: &str = "USER";
     const ADMIN: &str = "ADMIN";
-    const NATIVE_DENOM: &str = "denom";
+    const NATIVE_DENOM: &str = "ujuno";
 
     fn mock_app() -> App {
         AppBuilder::new().build(|router, _, storage| {
@@ -27,7 +27,7 @@ mod tests {
                     &Addr::unchecked(USER),
                     vec![Coin {
                         denom: NATIVE_DENOM.to_string(),
-                        amount: Uint128::new(1),
+                        amount: Uint128::new(1_000_000),
                     }],
                 )
                 .unwrap();
@@ -41,13 +41,22 @@ mod tests {
         let msg = InstantiateMsg { 
             blocks_per_year: 5048093, 
             meta_url: "example.com".to_string(),
-            denom: "ujuno".to_string(),
-            cost_for_6: Some(1), 
-            cost_for_5: Some(2), 
-            cost_for_4: Some(4), 
-            cost_for_3: Some(8), 
-            cost_for_2: Some(16), 
-            cost_for_1: Some(32),
+            denom: NATIVE_DENOM.to_string(),
+            prices: None,
+            grace_period: None,
+            redemption_period: None,
+            redemption_fee: None,
+            require_commitment: None,
+            min_commitment_age: None,
+            max_commitment_age: None,
+            subdomain_fee_bps: None,
+            auction_duration: None,
+            min_bid_increment_bps: None,
+            premium: None,
+            premium_days: None,
+            premium_decay: None,
+            max_registration_years: None,
+            extra_denoms: None,
         };
         let cw_template_contract_addr = app
             .instantiate_contract(
@@ -78,4 +87,143 @@ mod tests {
             app.execute(Addr::unchecked(ADMIN), cosmos_msg).unwrap();
         }
     }
+
+    mod send_nft {
+        use super::*;
+        use crate::msg::{Cw721ReceiveMsg, ExecuteMsg, OwnerResponse, QueryMsg, ReceiverExecuteMsg};
+        use cosmwasm_std::{
+            coins, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError,
+            StdResult,
+        };
+        use cw_storage_plus::Item;
+        use serde::{Deserialize, Serialize};
+
+        // what the mock receiver saw: the calling contract and the CW721 payload
+        #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+        struct Received {
+            caller: String,
+            msg: Cw721ReceiveMsg,
+        }
+
+        const RECEIVED: Item<Received> = Item::new("received");
+
+        fn receiver_execute(
+            deps: DepsMut,
+            _env: Env,
+            info: MessageInfo,
+            msg: ReceiverExecuteMsg,
+        ) -> StdResult<Response> {
+            let ReceiverExecuteMsg::ReceiveNft(msg) = msg;
+            if msg.msg == to_binary("reject")? {
+                return Err(StdError::generic_err("rejected"));
+            }
+            RECEIVED.save(deps.storage, &Received { caller: info.sender.to_string(), msg })?;
+            Ok(Response::new())
+        }
+
+        fn receiver_instantiate(
+            _deps: DepsMut,
+            _env: Env,
+            _info: MessageInfo,
+            _msg: Empty,
+        ) -> StdResult<Response> {
+            Ok(Response::new())
+        }
+
+        fn receiver_query(deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+            to_binary(&RECEIVED.may_load(deps.storage)?)
+        }
+
+        fn setup() -> (App, CwTemplateContract, Addr) {
+            let (mut app, cw_template_contract) = proper_instantiate();
+
+            let receiver_id = app.store_code(Box::new(ContractWrapper::new(
+                receiver_execute,
+                receiver_instantiate,
+                receiver_query,
+            )));
+            let receiver = app
+                .instantiate_contract(receiver_id, Addr::unchecked(ADMIN), &Empty {}, &[], "receiver", None)
+                .unwrap();
+
+            let msg = ExecuteMsg::RegisterName { name: String::from("testname") , years: 1 , records: None, addresses: None};
+            app.execute_contract(Addr::unchecked(USER), cw_template_contract.addr(), &msg, &coins(156250, NATIVE_DENOM))
+                .unwrap();
+
+            (app, cw_template_contract, receiver)
+        }
+
+        fn owner_of(app: &App, contract: &CwTemplateContract) -> Addr {
+            let res: OwnerResponse = app
+                .wrap()
+                .query_wasm_smart(contract.addr(), &QueryMsg::OwnerOf { token_id: String::from("testname") })
+                .unwrap();
+            res.owner
+        }
+
+        #[test]
+        fn calls_receiver() {
+            let (mut app, cw_template_contract, receiver) = setup();
+
+            let payload = to_binary("list it").unwrap();
+            let msg = ExecuteMsg::SendNft {
+                contract: receiver.to_string(),
+                token_id: String::from("TestName"),
+                msg: payload.clone(),
+                keep_records: None,
+            };
+            app.execute_contract(Addr::unchecked(USER), cw_template_contract.addr(), &msg, &[])
+                .unwrap();
+
+            assert_eq!(receiver, owner_of(&app, &cw_template_contract));
+
+            let received: Option<Received> = app.wrap().query_wasm_smart(&receiver, &Empty {}).unwrap();
+            assert_eq!(
+                Some(Received {
+                    caller: cw_template_contract.addr().to_string(),
+                    msg: Cw721ReceiveMsg {
+                        sender: USER.to_string(),
+                        token_id: String::from("testname"),
+                        msg: payload,
+                    },
+                }),
+                received
+            );
+        }
+
+        #[test]
+        fn receiver_failure_reverts_transfer() {
+            let (mut app, cw_template_contract, receiver) = setup();
+
+            let msg = ExecuteMsg::SendNft {
+                contract: receiver.to_string(),
+                token_id: String::from("testname"),
+                msg: to_binary("reject").unwrap(),
+                keep_records: None,
+            };
+            let _err = app
+                .execute_contract(Addr::unchecked(USER), cw_template_contract.addr(), &msg, &[])
+                .unwrap_err();
+
+            assert_eq!(Addr::unchecked(USER), owner_of(&app, &cw_template_contract));
+        }
+
+        #[test]
+        fn only_owner_can_send() {
+            let (mut app, cw_template_contract, receiver) = setup();
+
+            let msg = ExecuteMsg::SendNft {
+                contract: receiver.to_string(),
+                token_id: String::from("testname"),
+                msg: to_binary("list it").unwrap(),
+                keep_records: None,
+            };
+            let _err = app
+                .execute_contract(Addr::unchecked(ADMIN), cw_template_contract.addr(), &msg, &[])
+                .unwrap_err();
+
+            let received: Option<Received> = app.wrap().query_wasm_smart(&receiver, &Empty {}).unwrap();
+            assert_eq!(None, received);
+        }
+    }
 }
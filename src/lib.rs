@@ -1,8 +1,11 @@
+pub mod bech32;
 pub mod contract;
 mod error;
 pub mod helpers;
 pub mod integration_tests;
+pub mod migrations;
 pub mod msg;
+pub mod normalize;
 pub mod state;
 
 pub use crate::error::ContractError;
@@ -0,0 +1,239 @@
+use cosmwasm_std::{Order, StdResult, Storage};
+use cw_storage_plus::{Bound, Item, Map};
+
+use crate::error::ContractError;
+use crate::normalize::normalize_name;
+use crate::state::Name;
+
+// names an older layout stored under ids the current naming rules can't
+// place, kept apart from `jns()` so no handler takes them for real names.
+// They don't count as tokens and can't be renewed, transferred or updated.
+// The migration lists them in its `quarantined` attributes, the `Quarantined`
+// query pages through them and the contract owner either drops them or gives
+// them back to their owners under a valid name with `ReleaseQuarantined`.
+pub const QUARANTINED: Map<&str, Name> = Map::new("quarantined_names");
+
+// last legacy id rewritten by a migration that hasn't reached the end of
+// `jns` yet. Names are migrated in batches so a large registry doesn't have
+// to fit in one transaction; the next `migrate` call carries on after it.
+pub const MIGRATION_CURSOR: Item<String> = Item::new("migration_cursor");
+
+/// What a storage migration did with the names it found.
+#[derive(Default)]
+pub struct Migrated {
+    pub names: u64,
+    pub quarantined: Vec<String>,
+    /// Whether every name has been rewritten, or another batch is needed
+    pub done: bool,
+}
+
+/// Parses a `major.minor.patch` version string as stored by cw2.
+pub fn parse_version(version: &str) -> Result<(u64, u64, u64), ContractError> {
+    let invalid = || ContractError::InvalidVersion { version: version.to_string() };
+
+    let parts: Vec<&str> = version.split('.').collect();
+    if parts.len() != 3 {
+        return Err(invalid());
+    }
+
+    let mut numbers = [0u64; 3];
+    for (i, part) in parts.iter().enumerate() {
+        // drop any pre-release or build suffix on the patch number
+        let digits = part.split(['-', '+']).next().unwrap_or("");
+        numbers[i] = digits.parse().map_err(|_| invalid())?;
+    }
+
+    Ok((numbers[0], numbers[1], numbers[2]))
+}
+
+/// Storage layout written by version 0.1.0 of the contract.
+pub mod v0_1_0 {
+    use super::*;
+
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+
+    use cosmwasm_std::{Addr, Uint128};
+    use cw_storage_plus::{Item, Map};
+
+    use crate::contract::{
+        DEFAULT_AUCTION_DURATION, DEFAULT_MAX_COMMITMENT_AGE, DEFAULT_MAX_REGISTRATION_YEARS,
+        DEFAULT_MIN_BID_INCREMENT_BPS, DEFAULT_MIN_COMMITMENT_AGE,
+    };
+    use crate::state::{Approval, ChainAddress, PremiumDecay, PriceTier};
+
+    pub const VERSION: (u64, u64, u64) = (0, 1, 0);
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct State {
+        pub blocks_per_year: u64,
+        pub owner: Addr,
+        pub meta_url: String,
+        pub denom: String,
+        pub cost_for_6: u64,
+        pub cost_for_5: u64,
+        pub cost_for_4: u64,
+        pub cost_for_3: u64,
+        pub cost_for_2: u64,
+        pub cost_for_1: u64,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct Name {
+        pub id: String,
+        pub expires: u64,
+        pub owner: Addr,
+        pub approvals: Vec<Approval>,
+        pub avatar_url: Option<String>,
+        pub secret_address: Option<String>,
+        pub crypto_org_address: Option<String>,
+        pub starname_address: Option<String>,
+        pub persistence_address: Option<String>,
+        pub kava_address: Option<String>,
+        pub terra_address: Option<String>,
+        pub website: Option<String>,
+        pub email: Option<String>,
+        pub twitter: Option<String>,
+        pub telegram: Option<String>,
+        pub discord: Option<String>,
+        pub instagram: Option<String>,
+        pub reddit: Option<String>,
+    }
+
+    pub const STATE: Item<State> = Item::new("state");
+
+    pub const JNS: Map<&str, Name> = Map::new("jns");
+
+    impl From<State> for crate::state::State {
+        fn from(old: State) -> Self {
+            crate::state::State {
+                blocks_per_year: old.blocks_per_year,
+                owner: old.owner,
+                meta_url: old.meta_url,
+                denom: old.denom,
+                prices: vec![
+                    PriceTier { min_length: 1, cost: Uint128::from(old.cost_for_1) },
+                    PriceTier { min_length: 2, cost: Uint128::from(old.cost_for_2) },
+                    PriceTier { min_length: 3, cost: Uint128::from(old.cost_for_3) },
+                    PriceTier { min_length: 4, cost: Uint128::from(old.cost_for_4) },
+                    PriceTier { min_length: 5, cost: Uint128::from(old.cost_for_5) },
+                    PriceTier { min_length: 6, cost: Uint128::from(old.cost_for_6) },
+                ],
+                grace_period: 0,
+                redemption_period: 0,
+                redemption_fee: Uint128::zero(),
+                require_commitment: false,
+                min_commitment_age: DEFAULT_MIN_COMMITMENT_AGE,
+                max_commitment_age: DEFAULT_MAX_COMMITMENT_AGE,
+                subdomain_fee_bps: 0,
+                auction_duration: DEFAULT_AUCTION_DURATION,
+                min_bid_increment_bps: DEFAULT_MIN_BID_INCREMENT_BPS,
+                premium: Uint128::zero(),
+                premium_days: 0,
+                premium_decay: PremiumDecay::Linear,
+                max_registration_years: DEFAULT_MAX_REGISTRATION_YEARS,
+                extra_denoms: vec![],
+            }
+        }
+    }
+
+    impl From<Name> for crate::state::Name {
+        fn from(old: Name) -> Self {
+            let mut name = crate::state::Name {
+                id: old.id,
+                expires: old.expires,
+                owner: old.owner,
+                approvals: old.approvals,
+                records: vec![],
+                addresses: vec![],
+            };
+
+            // per chain fields are keyed by the chain's bech32 prefix, kept as
+            // they were since 0.1.0 never validated them
+            let chains = [
+                ("cro", old.crypto_org_address),
+                ("kava", old.kava_address),
+                ("persistence", old.persistence_address),
+                ("secret", old.secret_address),
+                ("star", old.starname_address),
+                ("terra", old.terra_address),
+            ];
+            for (chain, address) in chains {
+                if let Some(address) = address {
+                    name.addresses.push(ChainAddress { chain: chain.to_string(), address });
+                }
+            }
+
+            // the fixed profile fields become text records
+            let fields = [
+                ("avatar", old.avatar_url),
+                ("url", old.website),
+                ("email", old.email),
+                ("com.twitter", old.twitter),
+                ("org.telegram", old.telegram),
+                ("com.discord", old.discord),
+                ("com.instagram", old.instagram),
+                ("com.reddit", old.reddit),
+            ];
+            for (key, value) in fields {
+                if let Some(value) = value {
+                    name.set_record(key.to_string(), value);
+                }
+            }
+
+            name
+        }
+    }
+
+    /// Rewrites `STATE` and up to `limit` `JNS` entries into the current
+    /// layout, picking up after `MIGRATION_CURSOR` when an earlier call
+    /// stopped part way.
+    pub fn migrate(storage: &mut dyn Storage, limit: usize) -> StdResult<Migrated> {
+        let cursor = MIGRATION_CURSOR.may_load(storage)?;
+        // the first batch converts the config, later ones find it converted
+        if cursor.is_none() {
+            let state = STATE.load(storage)?;
+            crate::state::STATE.save(storage, &state.into())?;
+        }
+
+        // rewritten entries share the namespace, so only read past the cursor
+        let start = cursor.map(|key| Bound::ExclusiveRaw(key.into_bytes()));
+        let mut names: Vec<(String, Name)> = JNS
+            .range(storage, start, None, Order::Ascending)
+            .take(limit + 1)
+            .collect::<StdResult<_>>()?;
+
+        let mut migrated = Migrated { done: names.len() <= limit, ..Migrated::default() };
+        names.truncate(limit);
+        let last = names.last().map(|(key, _)| key.clone());
+
+        for (key, name) in names {
+            // the indexed save reads the previous value, which is still in the old layout
+            JNS.remove(storage, &key);
+
+            // 0.1.0 had no subdomains, so a dotted id is a top level name that
+            // the parent index would hand to whoever owns the part after the dot.
+            // Ids like "hello world" could never be looked up again either, as
+            // every handler normalizes the name it is given first.
+            if normalize_name(&key).ok().as_deref() != Some(key.as_str()) {
+                QUARANTINED.save(storage, &key, &name.into())?;
+                migrated.quarantined.push(key);
+                continue;
+            }
+
+            crate::state::jns().save(storage, &key, &name.into())?;
+            migrated.names += 1;
+        }
+
+        // v0.1.0 did not count its tokens, so the count starts with the first batch
+        let count = crate::state::TOKEN_COUNT.may_load(storage)?.unwrap_or_default();
+        crate::state::TOKEN_COUNT.save(storage, &(count + migrated.names))?;
+
+        match last {
+            Some(key) if !migrated.done => MIGRATION_CURSOR.save(storage, &key)?,
+            _ => MIGRATION_CURSOR.remove(storage),
+        }
+
+        Ok(migrated)
+    }
+}
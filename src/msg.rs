@@ -1,7 +1,7 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use cosmwasm_std::Addr;
-use crate::state::{ Name, Operator } ;
+use cosmwasm_std::{ to_binary, Addr, CosmosMsg, StdResult, Uint128, WasmMsg };
+use crate::state::{ Auction, ChainAddress, DenomPrices, Name, NameStatus, Operator, PremiumDecay, PriceTier, TextRecord } ;
 
 //REQUIRED BY CW721
 use cw_utils::Expiration;
@@ -12,66 +12,165 @@ pub struct InstantiateMsg {
     pub blocks_per_year: u64,
     pub meta_url: String,
     pub denom: String,
-    pub cost_for_6: Option<u64>,
-    pub cost_for_5: Option<u64>,
-    pub cost_for_4: Option<u64>,
-    pub cost_for_3: Option<u64>,
-    pub cost_for_2: Option<u64>,
-    pub cost_for_1: Option<u64>
+    /// Price schedule ordered by `min_length`, the first tier must start at 1
+    pub prices: Option<Vec<PriceTier>>,
+    /// Seconds after expiry only the previous owner can renew, defaults to 0
+    pub grace_period: Option<u64>,
+    /// Seconds after the grace period the previous owner can renew for `redemption_fee`, defaults to 0
+    pub redemption_period: Option<u64>,
+    pub redemption_fee: Option<Uint128>,
+    /// Only allow registration through CommitName / RevealName, defaults to false
+    pub require_commitment: Option<bool>,
+    /// Seconds before a commitment can be revealed, defaults to 60
+    pub min_commitment_age: Option<u64>,
+    /// Seconds a commitment stays valid, defaults to one day
+    pub max_commitment_age: Option<u64>,
+    /// Protocol fee on subdomain sales in basis points, defaults to 0
+    pub subdomain_fee_bps: Option<u64>,
+    /// Seconds an auction accepts bids, defaults to a week
+    pub auction_duration: Option<u64>,
+    /// How much a bid has to beat the previous one by in basis points, defaults to 500
+    pub min_bid_increment_bps: Option<u64>,
+    /// Added to the price of a name re-registered right after its redemption period, defaults to 0
+    pub premium: Option<Uint128>,
+    /// Days after the redemption period the premium takes to reach zero, defaults to 0
+    pub premium_days: Option<u64>,
+    /// Defaults to linear
+    pub premium_decay: Option<PremiumDecay>,
+    /// Furthest a name can be registered or renewed into the future, defaults to 10 years
+    pub max_registration_years: Option<u64>,
+    /// Other denoms names can be registered and renewed with, each with its own tiers
+    pub extra_denoms: Option<Vec<DenomPrices>>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {
+    /// Most legacy names rewritten by this call, defaults to 100. While names
+    /// remain the contract refuses to execute; migrate again to continue.
+    pub limit: Option<u32>,
+}
+
+/// Config fields the owner can change with `UpdateConfig`
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct ConfigUpdate {
+    pub meta_url: Option<String>,
+    /// Refused while price overrides exist, or unless a non-zero `premium` and
+    /// `redemption_fee` are set again with it. Subdomain prices keep their denom.
+    pub denom: Option<String>,
+    pub prices: Option<Vec<PriceTier>>,
+    pub grace_period: Option<u64>,
+    pub redemption_period: Option<u64>,
+    pub redemption_fee: Option<Uint128>,
+    pub require_commitment: Option<bool>,
+    pub min_commitment_age: Option<u64>,
+    pub max_commitment_age: Option<u64>,
+    pub subdomain_fee_bps: Option<u64>,
+    pub auction_duration: Option<u64>,
+    pub min_bid_increment_bps: Option<u64>,
+    pub premium: Option<Uint128>,
+    pub premium_days: Option<u64>,
+    pub premium_decay: Option<PremiumDecay>,
+    pub max_registration_years: Option<u64>,
+    /// Replaces the whole list, an empty one only accepts `denom`
+    pub extra_denoms: Option<Vec<DenomPrices>>,
+}
+
+// messages are decoded once per call, UpdateConfig being larger than the rest is fine
+#[allow(clippy::large_enum_variant)]
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
     SetBlocksPerYear { blocks_per_year: u64 },
     SetOwner { owner: Addr },
+    /// Owner only, any field left out keeps its current value
+    UpdateConfig(ConfigUpdate),
     RegisterName { 
         name: String, 
         years: u64, 
-        avatar_url: Option<String>,
-        secret_address: Option<String>, 
-        crypto_org_address: Option<String>, 
-        starname_address: Option<String>, 
-        persistence_address: Option<String>, 
-        kava_address: Option<String>,  
-        terra_address: Option<String>, 
-        website: Option<String>, 
-        email: Option<String>, 
-        twitter: Option<String>, 
-        telegram: Option<String>, 
-        discord: Option<String>, 
-        instagram: Option<String>, 
-        reddit: Option<String>,
+        records: Option<Vec<TextRecord>>,
+        addresses: Option<Vec<ChainAddress>>,
+    },
+    /// First half of a front-running safe registration.
+    /// `commitment` is the hex encoded sha256 of `"{name}:{owner}:{secret}"`.
+    /// Each commit also removes a few of the oldest expired commitments.
+    CommitName { commitment: String },
+    /// Registers the name of an earlier commitment by the sender once it is old enough
+    RevealName {
+        name: String,
+        secret: String,
+        years: u64,
+        records: Option<Vec<TextRecord>>,
+        addresses: Option<Vec<ChainAddress>>,
     },
+    /// Anyone can pay to extend a live name, it stays with its owner.
+    /// Once expired only the owner can renew it.
     AddTime { name : String, years: u64},
+    /// Makes a name owned by the sender the one `ReverseResolve` returns for them
+    SetPrimaryName { name: String },
+    /// Changes only the records and addresses listed, a `None` value removes the entry.
+    /// Approvals are kept.
     UpdateParams { 
         name: String, 
-        avatar_url: Option<String>, 
-        secret_address: Option<String>, 
-        crypto_org_address: Option<String>, 
-        starname_address: Option<String>, 
-        persistence_address: Option<String>, 
-        kava_address: Option<String>, 
-        terra_address: Option<String>, 
-        website: Option<String>, 
-        email: Option<String>, 
-        twitter: Option<String>, 
-        telegram: Option<String>, 
-        discord: Option<String>, 
-        instagram: Option<String>, 
-        reddit: Option<String>,
+        records: Option<Vec<RecordUpdate>>,
+        addresses: Option<Vec<AddressUpdate>>,
     },
+    /// Adds or replaces a single text record, keys are lowercase like `url` or `com.twitter`
+    SetRecord { name: String, key: String, value: String },
+    RemoveRecord { name: String, key: String },
+
+    /// Parent owner only. Creates `{label}.{parent}` for `owner`, expiring with the parent
+    /// unless an earlier `expires` in nanoseconds is given
+    CreateSubdomain { parent: String, label: String, owner: String, expires: Option<u64> },
+    /// Parent owner only. Moves the expiry of a subdomain out to the parent's,
+    /// or to an earlier `expires` in nanoseconds. It never shortens the subdomain.
+    ExtendSubdomain { name: String, expires: Option<u64> },
+    /// Parent owner only, removes the subdomain and every subdomain under it.
+    /// Refused while the subdomain or any subdomain under it was sold with BuySubdomain and is live.
+    RevokeSubdomain { name: String },
+    /// Parent owner only, puts subdomains of `parent` up for sale in the current `denom`
+    /// or stops sales with `None`
+    SetSubdomainPrice { parent: String, price: Option<Uint128> },
+    /// Buys `{label}.{parent}` at the parent's price, it expires with the parent.
+    /// The protocol fee stays with the contract, the rest goes to the parent owner.
+    BuySubdomain { parent: String, label: String },
+
+    /// Opens an auction on a name nobody can renew anymore. The contract owner can also open one
+    /// on a name that was never registered and pick the starting bid, which otherwise is a year's price
+    /// plus any expiry premium
+    StartAuction { name: String, min_bid: Option<Uint128> },
+    /// Escrows the `denom` funds sent as a bid, refunding the bidder it beats
+    Bid { name: String },
+    /// Anyone can close an auction once it ended, the highest bidder gets the name for a year
+    CloseAuction { name: String },
+
+    /// Owner only, keeps names from being registered by the public
+    AddReserved { names: Vec<String> },
+    /// Owner only
+    RemoveReserved { names: Vec<String> },
+    /// Owner only, registers a reserved name to `owner` without payment
+    MintReserved { name: String, owner: String, years: u64 },
+
+    /// Owner only, drops a legacy name quarantined by the migration. With `name` it is
+    /// registered again under that valid name for the same owner, records and expiry.
+    ReleaseQuarantined { id: String, name: Option<String> },
+
+    /// Owner only, sets the yearly cost of specific names for registration and renewal
+    SetPriceOverrides { prices: Vec<NamePrice> },
+    /// Owner only, the names go back to their length based price
+    RemovePriceOverrides { names: Vec<String> },
 
     /**
      * ALL THE CW721 STANDARD FUNCTIONS
      */
-    TransferNft { recipient: String, token_id: String },
+    /// Records and addresses are cleared unless `keep_records` is set
+    TransferNft { recipient: String, token_id: String, keep_records: Option<bool> },
     /// Send is a base message to transfer a token to a contract and trigger an action
     /// on the receiving contract.
     SendNft {
         contract: String,
         token_id: String,
-        message: Binary,
+        msg: Binary,
+        keep_records: Option<bool>,
     },
     /// Allows operator to transfer / send the token from the owner's account.
     /// If expiration is set, then this allowance has a time/height limit
@@ -99,8 +198,65 @@ pub enum ExecuteMsg {
 pub enum QueryMsg {
     GetOwner {},
     GetBlocksPerYear {},
+    /// Return type: `State`
+    GetConfig {},
+    /// Cost of registering or renewing `name` for `years`, in `denom` and every other accepted denom
+    PriceQuote { name: String, years: u64 },
+    /// Cost of registering `name` for `years` right now, including any expiry premium
+    /// Return type: `RegistrationQuoteResponse`
+    RegistrationQuote { name: String, years: u64 },
+    /// Names and subdomains like `team.alice` resolve while every name up the hierarchy is active
     ResolveName { name : String },
     ResolveAttributes { name : String },
+    /// Text records of a name, all of them unless `keys` is given
+    /// Return type: `RecordsResponse`
+    GetRecords { name: String, keys: Option<Vec<String>> },
+    /// Address of the name on another chain, `chain` is its bech32 prefix
+    /// Return type: `AddressResponse`
+    ResolveAddress { name: String, chain: String },
+    /// Direct subdomains of `parent`, including expired ones
+    /// Return type: `TokensResponse`
+    Subdomains {
+        parent: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Price of a subdomain of `parent` and how it is split
+    /// Return type: `SubdomainPriceResponse`
+    SubdomainPriceQuote { parent: String },
+    /// Open auction on a name with its current bid
+    /// Return type: `Auction`
+    Auction { name: String },
+    /// Open auctions ordered by name
+    /// Return type: `AuctionsResponse`
+    Auctions {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Reserved names ordered alphabetically
+    /// Return type: `ReservedResponse`
+    Reserved {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Legacy names the migration set aside, ordered by their raw id
+    /// Return type: `QuarantinedResponse`
+    Quarantined {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Names with their own price ordered alphabetically
+    /// Return type: `PriceOverridesResponse`
+    PriceOverrides {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Where the name is in its lifecycle, works for expired and unregistered names
+    /// Return type: `NameStatusResponse`
+    NameStatus { name: String },
+    /// Primary name of an address, if it still owns it
+    /// Return type: `ReverseResolveResponse`
+    ReverseResolve { address: String },
     OwnerOf {
         token_id: String,
     },
@@ -112,12 +268,86 @@ pub enum QueryMsg {
         limit: Option<u32>,
     },
     NumTokens {},
+    /// With Enumerable extension.
+    /// Returns all tokens owned by the given address, [] if unset.
+    /// Expired names are left out unless `include_expired` is set.
+    /// Return type: TokensResponse.
+    Tokens {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+        include_expired: Option<bool>,
+    },
+    /// With Enumerable extension.
+    /// Requires pagination. Lists all token_ids controlled by the contract.
+    /// Return type: TokensResponse.
+    AllTokens {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
     ContractInfo {},
     NftInfo {
         token_id: String,
     },
 }
 
+/// Yearly cost of a single name
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NamePrice {
+    pub name: String,
+    pub cost: Uint128,
+}
+
+/// Sets `key` to `value`, or removes it when `value` is `None`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RecordUpdate {
+    pub key: String,
+    pub value: Option<String>,
+}
+
+/// Sets the address for `chain`, or removes it when `address` is `None`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AddressUpdate {
+    pub chain: String,
+    pub address: Option<String>,
+}
+
+/// Cw721ReceiveMsg should be de/serialized under `ReceiveNft()` variant in a ExecuteMsg
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct Cw721ReceiveMsg {
+    pub sender: String,
+    pub token_id: String,
+    pub msg: Binary,
+}
+
+impl Cw721ReceiveMsg {
+    /// serializes the message
+    pub fn into_binary(self) -> StdResult<Binary> {
+        let msg = ReceiverExecuteMsg::ReceiveNft(self);
+        to_binary(&msg)
+    }
+
+    /// creates a cosmos_msg sending this struct to the named contract
+    pub fn into_cosmos_msg<T: Into<String>>(self, contract_addr: T) -> StdResult<CosmosMsg> {
+        let msg = self.into_binary()?;
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg,
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
+}
+
+/// This is just a helper to properly serialize the above message.
+/// The actual receiver should include this variant in the larger ExecuteMsg enum
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiverExecuteMsg {
+    ReceiveNft(Cw721ReceiveMsg),
+}
+
 // Blocks Per Year response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct BlocksResponse {
@@ -137,6 +367,36 @@ pub struct NameResponse {
     pub name: Name,
 }
 
+// Records Response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RecordsResponse {
+    pub records: Vec<TextRecord>,
+}
+
+// Address Response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AddressResponse {
+    pub chain: String,
+    pub address: String,
+}
+
+// Name Status Response, the period ends are nanoseconds like `expires`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NameStatusResponse {
+    pub name: String,
+    pub status: NameStatus,
+    pub owner: Option<Addr>,
+    pub expires: Option<u64>,
+    pub grace_ends: Option<u64>,
+    pub redemption_ends: Option<u64>,
+}
+
+// Reverse Resolve Response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReverseResolveResponse {
+    pub name: Option<String>,
+}
+
 // Name Response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ApprovedForAllResponse {
@@ -146,7 +406,92 @@ pub struct ApprovedForAllResponse {
 // Name Response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct NumTokensResponse {
-    pub tokens: u32,
+    pub tokens: u64,
+}
+
+// Price Quote Response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceResponse {
+    pub name: String,
+    pub years: u64,
+    pub denom: String,
+    pub cost_per_year: Uint128,
+    pub total: Uint128,
+    pub other_denoms: Vec<DenomQuote>,
+    /// Set when extra denoms are accepted but this name can only be paid in
+    /// `denom`. Redemption fees are only charged in `denom` as well.
+    pub main_denom_only: Option<MainDenomOnly>,
+}
+
+/// Why a quote lists no `other_denoms`, these amounts only have a price in the main `denom`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MainDenomOnly {
+    /// The name has its own price, set with `SetPriceOverrides`
+    PriceOverride,
+    /// An expiry premium is due on the name
+    ExpiryPremium,
+}
+
+/// Price of a name in one of the extra denoms
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DenomQuote {
+    pub denom: String,
+    pub cost_per_year: Uint128,
+    pub total: Uint128,
+}
+
+// Subdomain Price Response, `price` is `protocol_fee` plus `parent_share`.
+// Subdomains are only sold in `denom`, never in the extra denoms.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SubdomainPriceResponse {
+    pub parent: String,
+    pub denom: String,
+    pub price: Uint128,
+    pub protocol_fee: Uint128,
+    pub parent_share: Uint128,
+}
+
+// Registration Quote Response, `total` is `base` plus `premium`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RegistrationQuoteResponse {
+    pub name: String,
+    pub years: u64,
+    pub denom: String,
+    pub base: Uint128,
+    pub premium: Uint128,
+    pub total: Uint128,
+    /// Empty while `main_denom_only` is set
+    pub other_denoms: Vec<DenomQuote>,
+    pub main_denom_only: Option<MainDenomOnly>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceOverridesResponse {
+    pub prices: Vec<NamePrice>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReservedResponse {
+    pub names: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct QuarantinedResponse {
+    pub names: Vec<Name>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AuctionsResponse {
+    pub auctions: Vec<Auction>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokensResponse {
+    /// Contains all token_ids in lexicographical ordering
+    /// If there are more than `limit`, use `start_after` in future queries
+    /// to achieve pagination.
+    pub tokens: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -0,0 +1,123 @@
+use crate::error::ContractError;
+
+pub const MIN_NAME_LENGTH: usize = 1;
+pub const MAX_NAME_LENGTH: usize = 63;
+/// Longest name including all of its subdomain labels and dots.
+pub const MAX_FULL_NAME_LENGTH: usize = 253;
+
+/// Only lowercase ascii letters, digits and inner hyphens can make up a name.
+/// Keeping to ascii rules out look-alike letters from other scripts as well as
+/// whitespace, control and zero-width characters.
+fn is_allowed(c: char) -> bool {
+    c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-'
+}
+
+/// Returns the canonical form of `name` used as its key in storage, or an error
+/// explaining why it can never be registered.
+pub fn normalize_name(name: &str) -> Result<String, ContractError> {
+    let invalid = |reason: &str| ContractError::InvalidName {
+        name: name.to_string(),
+        reason: reason.to_string(),
+    };
+
+    let normalized = name.to_ascii_lowercase();
+
+    if let Some(c) = normalized.chars().find(|c| !is_allowed(*c)) {
+        return Err(invalid(&format!("character {:?} is not allowed", c)));
+    }
+
+    let length = normalized.len();
+    if length < MIN_NAME_LENGTH {
+        return Err(invalid(&format!("must be at least {} characters", MIN_NAME_LENGTH)));
+    }
+    if length > MAX_NAME_LENGTH {
+        return Err(invalid(&format!("must be at most {} characters", MAX_NAME_LENGTH)));
+    }
+
+    if normalized.starts_with('-') || normalized.ends_with('-') {
+        return Err(invalid("cannot start or end with a hyphen"));
+    }
+
+    Ok(normalized)
+}
+
+/// Normalizes every dot separated label of a name like `team.alice`, where
+/// `team` is a subdomain of `alice`.
+pub fn normalize_full_name(name: &str) -> Result<String, ContractError> {
+    if name.len() > MAX_FULL_NAME_LENGTH {
+        return Err(ContractError::InvalidName {
+            name: name.to_string(),
+            reason: format!("must be at most {} characters", MAX_FULL_NAME_LENGTH),
+        });
+    }
+
+    let labels = name
+        .split('.')
+        .map(normalize_name)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(labels.join("."))
+}
+
+/// The name a subdomain lives under, `None` for top level names.
+pub fn parent_name(name: &str) -> Option<&str> {
+    name.split_once('.').map(|(_, parent)| parent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_and_lowercases() {
+        assert_eq!("testname", normalize_name("TestName").unwrap());
+        assert_eq!("jackal-labs", normalize_name("jackal-labs").unwrap());
+        assert_eq!("a", normalize_name("a").unwrap());
+        assert_eq!("007", normalize_name("007").unwrap());
+        assert_eq!("x".repeat(MAX_NAME_LENGTH), normalize_name(&"x".repeat(MAX_NAME_LENGTH)).unwrap());
+    }
+
+    #[test]
+    fn rejects_invalid_names() {
+        let invalid = [
+            "",
+            "two words",
+            " padded",
+            "dotted.name",
+            "under_score",
+            "-leading",
+            "trailing-",
+            "tab\tname",
+            "zero\u{200b}width",
+            "bell\u{7}",
+            // cyrillic 'а' looks just like the latin one
+            "j\u{430}ckal",
+            "caf\u{e9}",
+        ];
+        for name in invalid.iter() {
+            match normalize_name(name) {
+                Err(ContractError::InvalidName { .. }) => {}
+                res => panic!("{:?} must be rejected, got {:?}", name, res),
+            }
+        }
+
+        assert!(normalize_name(&"x".repeat(MAX_NAME_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn full_names() {
+        assert_eq!("team.alice", normalize_full_name("Team.Alice").unwrap());
+        assert_eq!("alice", normalize_full_name("alice").unwrap());
+        assert_eq!("dev.team.alice", normalize_full_name("dev.team.alice").unwrap());
+
+        for name in ["", ".alice", "team.", "team..alice", "te am.alice", "-team.alice"].iter() {
+            assert!(normalize_full_name(name).is_err(), "{:?} must be rejected", name);
+        }
+        let long = vec!["x".repeat(MAX_NAME_LENGTH); 4].join(".");
+        assert!(normalize_full_name(&long).is_err());
+
+        assert_eq!(Some("team.alice"), parent_name("dev.team.alice"));
+        assert_eq!(Some("alice"), parent_name("team.alice"));
+        assert_eq!(None, parent_name("alice"));
+    }
+}
@@ -1,10 +1,12 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{ Addr, CanonicalAddr } ;
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{ Addr, CanonicalAddr, Coin, Empty, StdResult, Storage, Uint128 } ;
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 use cw_utils::Expiration;
 
+use crate::normalize::parent_name;
+
 pub const OPERATOR_PREFIX: &[u8] = b"operators";
 
 
@@ -17,13 +19,65 @@ pub struct State {
 
     pub denom: String,          // accepted token denom
 
-    //prices to register a name per character count
-    pub cost_for_6: u64,
-    pub cost_for_5: u64,
-    pub cost_for_4: u64,
-    pub cost_for_3: u64,
-    pub cost_for_2: u64,
-    pub cost_for_1: u64
+    //prices to register a name per character count, ordered by min_length
+    pub prices: Vec<PriceTier>,
+
+    // seconds after expiry during which only the previous owner can renew
+    pub grace_period: u64,
+    // seconds after the grace period during which the previous owner can still renew for an extra fee
+    pub redemption_period: u64,
+    pub redemption_fee: Uint128,
+
+    // when set, names can only be registered through CommitName / RevealName
+    pub require_commitment: bool,
+    // seconds a commitment has to wait before it can be revealed, and how long it stays usable
+    pub min_commitment_age: u64,
+    pub max_commitment_age: u64,
+
+    // share of every subdomain sale kept by the contract, in basis points
+    pub subdomain_fee_bps: u64,
+
+    // seconds an auction runs, and how much each bid has to beat the last one by in basis points
+    pub auction_duration: u64,
+    pub min_bid_increment_bps: u64,
+
+    // extra cost of re-registering a name once it is released, fading out over `premium_days`
+    pub premium: Uint128,
+    pub premium_days: u64,
+    pub premium_decay: PremiumDecay,
+
+    // how many years ahead registrations and renewals can push `expires`
+    pub max_registration_years: u64,
+
+    // other denoms registrations and renewals can be paid in, fees, premiums,
+    // auctions and subdomain sales stay in `denom`
+    pub extra_denoms: Vec<DenomPrices>,
+}
+
+/// How the expiry premium fades out.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PremiumDecay {
+    /// drops by the same amount every second
+    Linear,
+    /// halves every day, shifted so it still reaches zero at the end
+    Exponential,
+}
+
+/// A denom names can be paid in besides the main one, like an IBC denom,
+/// priced by its own tiers.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DenomPrices {
+    pub denom: String,
+    pub prices: Vec<PriceTier>,
+}
+
+/// Yearly cost for every name with at least `min_length` characters,
+/// up to the next tier's threshold.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceTier {
+    pub min_length: u32,
+    pub cost: Uint128,
 }
 
 
@@ -45,39 +99,128 @@ pub struct Name {
 
     pub approvals: Vec<Approval>,   // NFT stuff
 
-    // a URL to an avatar image to be assocaited with the name
-    pub avatar_url: Option<String>, 
+    // text records such as `avatar`, `url` or `com.twitter`, sorted by key
+    pub records: Vec<TextRecord>,
+
+    // addresses on other chains keyed by their bech32 prefix, sorted by chain
+    pub addresses: Vec<ChainAddress>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TextRecord {
+    pub key: String,
+    pub value: String,
+}
+
+/// An address on another chain, `chain` is its bech32 prefix like `osmo` or `terra`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ChainAddress {
+    pub chain: String,
+    pub address: String,
+}
+
+/// Where a name is in its lifecycle relative to its expiry.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NameStatus {
+    /// registered and resolving
+    Active,
+    /// expired, only the previous owner can renew it
+    Grace,
+    /// grace is over, the previous owner can still renew it by paying the redemption fee
+    Redemption,
+    /// anyone can register it
+    Available,
+}
+
+impl Name {
+    /// End of the grace period in nanoseconds, like `expires`.
+    pub fn grace_ends(&self, state: &State) -> u64 {
+        self.expires.saturating_add(state.grace_period.saturating_mul(1_000_000_000))
+    }
+
+    /// End of the redemption period in nanoseconds, like `expires`.
+    pub fn redemption_ends(&self, state: &State) -> u64 {
+        self.grace_ends(state).saturating_add(state.redemption_period.saturating_mul(1_000_000_000))
+    }
+
+    pub fn address(&self, chain: &str) -> Option<&str> {
+        self.addresses.iter().find(|a| a.chain == chain).map(|a| a.address.as_str())
+    }
+
+    /// Adds or replaces an address, keeping them sorted by chain.
+    pub fn set_address(&mut self, chain: String, address: String) {
+        match self.addresses.binary_search_by(|a| a.chain.as_str().cmp(&chain)) {
+            Ok(i) => self.addresses[i].address = address,
+            Err(i) => self.addresses.insert(i, ChainAddress { chain, address }),
+        }
+    }
+
+    /// Returns whether an address was removed.
+    pub fn remove_address(&mut self, chain: &str) -> bool {
+        let len = self.addresses.len();
+        self.addresses.retain(|a| a.chain != chain);
+        self.addresses.len() != len
+    }
+
+    pub fn record(&self, key: &str) -> Option<&str> {
+        self.records.iter().find(|r| r.key == key).map(|r| r.value.as_str())
+    }
+
+    /// Adds or replaces a record, keeping them sorted by key.
+    pub fn set_record(&mut self, key: String, value: String) {
+        match self.records.binary_search_by(|r| r.key.as_str().cmp(&key)) {
+            Ok(i) => self.records[i].value = value,
+            Err(i) => self.records.insert(i, TextRecord { key, value }),
+        }
+    }
 
-    // other chains that had to be quirky and get their own coin_id instead of just going with the default and being a pain in my ass
-    pub secret_address: Option<String>, 
-    pub crypto_org_address: Option<String>,
-    pub starname_address: Option<String>,
-    pub persistence_address: Option<String>,
-    pub kava_address: Option<String>,
-    pub terra_address: Option<String>, 
+    /// Returns whether a record was removed.
+    pub fn remove_record(&mut self, key: &str) -> bool {
+        let len = self.records.len();
+        self.records.retain(|r| r.key != key);
+        self.records.len() != len
+    }
+
+    /// The name this one is a subdomain of, `None` for top level names.
+    pub fn parent(&self) -> Option<&str> {
+        parent_name(&self.id)
+    }
 
-    /// socials ///
-    pub website: Option<String>,
-    pub email: Option<String>,
-    pub twitter: Option<String>,
-    pub telegram: Option<String>,
-    pub discord: Option<String>,
-    pub instagram: Option<String>,
-    pub reddit: Option<String>
+    pub fn status(&self, state: &State, now: u64) -> NameStatus {
+        if now < self.expires {
+            NameStatus::Active
+        } else if self.parent().is_some() {
+            // subdomains live at their parent's discretion and have no grace
+            NameStatus::Available
+        } else if now < self.grace_ends(state) {
+            NameStatus::Grace
+        } else if now < self.redemption_ends(state) {
+            NameStatus::Redemption
+        } else {
+            NameStatus::Available
+        }
+    }
 }
 
 impl std::fmt::Display for Name {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "Name (id: {}, expires: {}, owner: {}, avatar_url: {:?}, website: {:?}, email: {:?}, twitter: {:?}, telegram: {:?}, discord: {:?}, instagram: {:?}, reddit: {:?})", self.id, self.expires, self.owner, self.avatar_url, self.website, self.email, self.twitter, self.telegram, self.discord, self.instagram, self.reddit)
+        write!(f, "Name (id: {}, expires: {}, owner: {}, records: {:?}, addresses: {:?})", self.id, self.expires, self.owner, self.records, self.addresses)
     }
 }
 
-impl Into<String> for Name {
-    fn into(self: Name) -> String {
-        format!("Name (id: {}, expires: {}, owner: {}, avatar_url: {:?}, website: {:?}, email: {:?}, twitter: {:?}, telegram: {:?}, discord: {:?}, instagram: {:?}, reddit: {:?})", self.id, self.expires, self.owner, self.avatar_url, self.website, self.email, self.twitter, self.telegram, self.discord, self.instagram, self.reddit)
+impl From<Name> for String {
+    fn from(name: Name) -> String {
+        format!("Name (id: {}, expires: {}, owner: {}, records: {:?}, addresses: {:?})", name.id, name.expires, name.owner, name.records, name.addresses)
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Commitment {
+    pub owner: Addr,
+    pub time: u64,                  // block time in nanoseconds the commitment was made
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Operator {
     pub owner: String,
@@ -88,5 +231,95 @@ pub const OPERATORS: Map<String, Vec<Operator>> = Map::new("operators");
 
 pub const STATE: Item<State> = Item::new("state");
 
-pub const JNS: Map<&str, Name> = Map::new("jns");
+// number of tokens stored in JNS
+pub const TOKEN_COUNT: Item<u64> = Item::new("num_tokens");
+
+pub fn num_tokens(storage: &dyn Storage) -> StdResult<u64> {
+    Ok(TOKEN_COUNT.may_load(storage)?.unwrap_or_default())
+}
+
+pub fn increment_tokens(storage: &mut dyn Storage) -> StdResult<u64> {
+    let val = num_tokens(storage)? + 1;
+    TOKEN_COUNT.save(storage, &val)?;
+    Ok(val)
+}
+
+pub fn decrement_tokens(storage: &mut dyn Storage) -> StdResult<u64> {
+    let val = num_tokens(storage)?.saturating_sub(1);
+    TOKEN_COUNT.save(storage, &val)?;
+    Ok(val)
+}
+
+pub struct NameIndexes<'a> {
+    pub owner: MultiIndex<'a, Addr, Name, String>,
+    // top level names are all indexed under ""
+    pub parent: MultiIndex<'a, String, Name, String>,
+}
+
+impl<'a> IndexList<Name> for NameIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Name>> + '_> {
+        let v: Vec<&dyn Index<Name>> = vec![&self.owner, &self.parent];
+        Box::new(v.into_iter())
+    }
+}
+
+// all registered names and subdomains, indexed by owner so holders can list
+// theirs and by parent so subdomains can be walked
+pub fn jns<'a>() -> IndexedMap<'a, &'a str, Name, NameIndexes<'a>> {
+    let indexes = NameIndexes {
+        owner: MultiIndex::new(|n: &Name| n.owner.clone(), "jns", "jns__owner"),
+        parent: MultiIndex::new(|n: &Name| n.parent().unwrap_or_default().to_string(), "jns", "jns__parent"),
+    };
+    IndexedMap::new("jns", indexes)
+}
+
+pub struct CommitmentIndexes<'a> {
+    pub time: MultiIndex<'a, u64, Commitment, String>,
+}
+
+impl<'a> IndexList<Commitment> for CommitmentIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Commitment>> + '_> {
+        let v: Vec<&dyn Index<Commitment>> = vec![&self.time];
+        Box::new(v.into_iter())
+    }
+}
+
+// pending registrations keyed by their hex encoded commitment hash, indexed by
+// time so the oldest ones can be pruned once they expired
+pub fn commitments<'a>() -> IndexedMap<'a, &'a str, Commitment, CommitmentIndexes<'a>> {
+    let indexes = CommitmentIndexes {
+        time: MultiIndex::new(|c: &Commitment| c.time, "commitments", "commitments__time"),
+    };
+    IndexedMap::new("commitments", indexes)
+}
+
+/// An open English auction, bids are escrowed by the contract until they are
+/// outbid or the auction closes.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Auction {
+    pub name: String,
+    pub denom: String,              // bids are taken in the denom the auction opened with
+    pub min_bid: Uint128,
+    pub highest_bid: Uint128,
+    pub highest_bidder: Option<Addr>,
+    pub ends: u64,                  // block time in nanoseconds bidding closes
+}
+
+// open auctions keyed by name
+pub const AUCTIONS: Map<&str, Auction> = Map::new("auctions");
+
+// yearly cost of specific names, taking precedence over the length based tiers
+pub const PRICE_OVERRIDES: Map<&str, Uint128> = Map::new("price_overrides");
+
+// names the public can't register, only the contract owner can mint them
+pub const RESERVED: Map<&str, Empty> = Map::new("reserved");
+
+// price parent owners sell their subdomains for, in the denom set when the price was
+pub const SUBDOMAIN_PRICES: Map<&str, Coin> = Map::new("subdomain_prices");
+
+// subdomains bought with BuySubdomain, their parent can't revoke them before they expire
+pub const SOLD_SUBDOMAINS: Map<&str, Empty> = Map::new("sold_subdomains");
+
+// reverse records, the primary name chosen by an address
+pub const PRIMARY_NAMES: Map<&Addr, String> = Map::new("primary_names");
 